@@ -0,0 +1,27 @@
+//! Compares `FixedBaseTable::exp` against `exp` for commitment-sized exponents on `ClassyGroup`.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::Rng;
+use rug::integer::Order;
+use rug::Integer;
+use supersonic::groups::classgroupsti::ClassyGroup;
+use supersonic::groups::fixed_base::FixedBaseTable;
+use supersonic::groups::{Group, HashToGroup};
+
+fn bench_fixed_base(c: &mut Criterion) {
+  let mut group = c.benchmark_group("fixed_base");
+  group.sample_size(10);
+  let g = ClassyGroup::hash_to_groupnew(b"fixed base");
+  for &bits in &[4096u32, 16384] {
+    let digits: Vec<u8> = (0..bits / 8).map(|_| rand::thread_rng().gen()).collect();
+    let n = Integer::from_digits(&digits, Order::Msf);
+    let table = FixedBaseTable::<ClassyGroup>::new(&g, bits, 1 << 12);
+    group.bench_with_input(BenchmarkId::new("exp", bits), &bits, |b, _| {
+      b.iter(|| ClassyGroup::exp(&g, &n).unwrap())
+    });
+    group.bench_with_input(BenchmarkId::new("table", bits), &bits, |b, _| b.iter(|| table.exp(&n)));
+  }
+  group.finish();
+}
+
+criterion_group!(benches, bench_fixed_base);
+criterion_main!(benches);
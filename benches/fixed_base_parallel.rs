@@ -0,0 +1,35 @@
+//! Compares `FixedBaseTable::exp` on one thread against all threads on `ClassyGroup`. Tables are
+//! split into chunks of at least `2^window` windows, one per thread.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::Rng;
+use rayon::ThreadPoolBuilder;
+use rug::integer::Order;
+use rug::Integer;
+use supersonic::groups::classgroupsti::ClassyGroup;
+use supersonic::groups::fixed_base::FixedBaseTable;
+use supersonic::groups::HashToGroup;
+
+fn bench_fixed_base_parallel(c: &mut Criterion) {
+  let mut group = c.benchmark_group("fixed_base_parallel");
+  group.sample_size(10);
+  let g = ClassyGroup::hash_to_groupnew(b"fixed base");
+  let single = ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+  let all = ThreadPoolBuilder::new().build().unwrap();
+  // With room for 2^12 elements, these take windows of 6, 7 and 8 bits, which makes for at most
+  // 6, 10 and 16 chunks.
+  for &bits in &[2048u32, 8192, 32768] {
+    let digits: Vec<u8> = (0..bits / 8).map(|_| rand::thread_rng().gen()).collect();
+    let n = Integer::from_digits(&digits, Order::Msf);
+    let table = FixedBaseTable::<ClassyGroup>::new(&g, bits, 1 << 12);
+    group.bench_with_input(BenchmarkId::new("one_thread", bits), &bits, |b, _| {
+      b.iter(|| single.install(|| table.exp(&n)))
+    });
+    group.bench_with_input(BenchmarkId::new("all_threads", bits), &bits, |b, _| {
+      b.iter(|| all.install(|| table.exp(&n)))
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, bench_fixed_base_parallel);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+//! Compares `multi_exp_general` against one `exp` per base on `ClassyGroup`.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::Rng;
+use rug::integer::Order;
+use rug::Integer;
+use supersonic::groups::classgroupsti::ClassyGroup;
+use supersonic::groups::{multi_exp_general, Group, HashToGroup};
+
+// Inputs shaped like a batch of commitments: `n` generators and 256-bit exponents.
+fn inputs(n: usize) -> (Vec<<ClassyGroup as Group>::Elem>, Vec<Integer>) {
+  let bases = (0..n as u64).map(|i| ClassyGroup::hash_to_groupnew(&i.to_be_bytes())).collect();
+  let exps = (0..n)
+    .map(|_| Integer::from_digits(&rand::thread_rng().gen::<[u8; 32]>(), Order::Msf))
+    .collect();
+  (bases, exps)
+}
+
+fn bench_multi_exp(c: &mut Criterion) {
+  let mut group = c.benchmark_group("multi_exp");
+  group.sample_size(10);
+  for &n in &[4, 16, 64] {
+    let (bases, exps) = inputs(n);
+    group.bench_with_input(BenchmarkId::new("naive", n), &n, |b, _| {
+      b.iter(|| {
+        bases.iter().zip(&exps).fold(ClassyGroup::idnew(), |acc, (x, e)| {
+          ClassyGroup::opnew(&acc, &ClassyGroup::exp(x, e).unwrap())
+        })
+      })
+    });
+    group.bench_with_input(BenchmarkId::new("multi_exp_general", n), &n, |b, _| {
+      b.iter(|| multi_exp_general::<ClassyGroup>(&bases, &exps))
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, bench_multi_exp);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+//! Errors for operations on untrusted input, so that callers can reject malformed elements,
+//! polynomials and proofs instead of panicking on them.
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+  /// A value does not describe an element of the group.
+  InvalidElement,
+  /// A polynomial has more coefficients than the public parameters allow, or the parameters
+  /// cannot support the requested degree.
+  DegreeTooLarge,
+  /// The security parameter `lambda` is not the bit size of the group.
+  UnsupportedLambda,
+  /// The public parameters are not related as `PolyComm::setup` derives them.
+  InvalidParameters,
+  /// A coefficient exceeds the bound `PP::b` in absolute value.
+  CoefficientOutOfBound,
+  /// A proof or an opening does not verify.
+  BadProof,
+  /// Bytes are not the canonical encoding of the expected object.
+  Deserialization(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::InvalidElement => write!(f, "invalid group element"),
+      Error::DegreeTooLarge => write!(f, "degree too large for the public parameters"),
+      Error::UnsupportedLambda => write!(f, "lambda does not match the group size"),
+      Error::InvalidParameters => write!(f, "inconsistent public parameters"),
+      Error::CoefficientOutOfBound => write!(f, "coefficient out of bound"),
+      Error::BadProof => write!(f, "proof does not verify"),
+      Error::Deserialization(reason) => write!(f, "deserialization failed: {}", reason),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,426 @@
+//! Class group of binary quadratic forms implemented directly on `rug` integers, whose
+//! discriminant is chosen by the user instead of being fixed by a dependency.
+use super::{ElemFrom, ElemToBytes, Group, HashPrime, HashToGroup, UnknownOrderGroup};
+use crate::error::{Error, Result};
+use crate::hash::{hash_to_prime, hash_to_prime_bytes};
+use crate::util::{append_integer, int, read_integer, TypeRep};
+use lazy_static::lazy_static;
+use rug::integer::{IsPrime, Order};
+use rug::Integer;
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// The discriminant of a `ClassGroup`, fixed at the type level so that groups of different
+/// discriminants are different types, and so are their elements.
+///
+/// A deployment with its own transparent setup derives a discriminant from a public seed with
+/// `generate_discriminant`, e.g. in a `lazy_static`, and returns it from `discriminant`.
+pub trait Discriminant: Clone + Debug + Eq + Hash + Send + Sync + 'static {
+  /// Returns the discriminant, which has to be negative and `1 (mod 8)`.
+  fn discriminant() -> &'static Integer;
+}
+
+/// The discriminant `CLASS_GROUP_DISCRIMINANT`, which `ClassyGroup` is fixed to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DefaultDiscriminant {}
+
+impl Discriminant for DefaultDiscriminant {
+  fn discriminant() -> &'static Integer {
+    &CLASS_GROUP_DISCRIMINANT
+  }
+}
+
+/// Class group of discriminant `D::discriminant()`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClassGroup<D: Discriminant = DefaultDiscriminant>(PhantomData<D>);
+
+/// The 2048-bit discriminant `classygroup` hard-codes as `CLASS_GROUP_DISCRIMINANT`.
+const CLASS_GROUP_DISCRIMINANT_DECIMAL: &str =
+  "-30616069034807523947093657516320815215492876376165067902716988657802400037331914448218251590830\
+  110218951921584943041318477665819248197627672077800926180883263030484171136687216122364364500191\
+  669694934234972248705063117104912335573294798164577233813687887340799331656530421457186687277652\
+  680575673207678516369650123480826989387975548598309959486361425021860161020248607833276306314923\
+  730985457097270235056741177973437257375484057013831031775435913701351265592632577304892671805069\
+  109294533717273440872863614264045883351603859982809886032974356390209112956520259677617027017014\
+  711623966286152805654229445219531956098223";
+
+lazy_static! {
+  pub static ref CLASS_GROUP_DISCRIMINANT: Integer =
+    Integer::from_str(CLASS_GROUP_DISCRIMINANT_DECIMAL).unwrap();
+}
+
+// Number of Miller-Rabin rounds when searching for a discriminant.
+const MILLER_RABIN_REPS: u32 = 50;
+
+/// A reduced form `(a, b, c)` with `b^2 - 4ac` equal to the discriminant `D::discriminant()`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClassGroupElem<D: Discriminant = DefaultDiscriminant> {
+  pub a: Integer,
+  pub b: Integer,
+  pub c: Integer,
+  disc: PhantomData<D>,
+}
+
+fn form<D: Discriminant>(a: Integer, b: Integer, c: Integer) -> ClassGroupElem<D> {
+  ClassGroupElem { a, b, c, disc: PhantomData }
+}
+
+/// Deterministically derives a discriminant `-p` of `bits` bits from a public `seed`.
+///
+/// `p` is the first probable prime with `p = 7 (mod 8)` among the `bits`-bit candidates
+/// `SHA256(seed || counter || 0) || SHA256(seed || counter || 1) || ...`, so that the class
+/// number is odd and `unknown_order_elem` exists.
+pub fn generate_discriminant(seed: &[u8], bits: u32) -> Integer {
+  assert!(bits >= 8, "discriminant too small");
+  let mut counter = 0u64;
+  loop {
+    let mut bytes = Vec::new();
+    for block in 0..(bits as u64).div_ceil(256) {
+      let mut hasher = Sha256::new();
+      hasher.update(seed);
+      hasher.update(counter.to_be_bytes());
+      hasher.update(block.to_be_bytes());
+      bytes.extend_from_slice(&hasher.finalize());
+    }
+    let mut p = Integer::from_digits(&bytes[..], Order::Msf).keep_bits(bits);
+    p.set_bit(bits - 1, true).set_bit(2, true).set_bit(1, true).set_bit(0, true);
+    if p.is_probably_prime(MILLER_RABIN_REPS) != IsPrime::No {
+      return -p;
+    }
+    counter += 1;
+  }
+}
+
+/// Maps `t` to a form `(a, b, c)` of discriminant `disc`, which callers still have to reduce.
+///
+/// `a` is the first hashed prime `a = 3 (mod 4)` for which `disc` is a square modulo `a`, and `b`
+/// is the odd square root of `disc` modulo `a`, which fixes `c = (b^2 - disc) / 4a`. Nobody learns
+/// a relation between the forms of different inputs, because their `a` are independent primes.
+pub fn hash_to_form(t: &[u8], disc: &Integer) -> (Integer, Integer, Integer) {
+  let mut counter = 0u64;
+  loop {
+    let a = hash_to_prime(&[t, &counter.to_be_bytes()[..]].concat());
+    counter += 1;
+    if a.mod_u(4) != 3 || disc.jacobi(&a) != 1 {
+      continue;
+    }
+    let mut b = disc.clone().pow_mod(&int(int(&a + 1) / 4), &a).unwrap();
+    if b.is_even() {
+      b = int(&a - &b);
+    }
+    let c = int(int(&b * &b) - disc) / int(&a * 4);
+    return (a, b, c);
+  }
+}
+
+/// Encodes the form `(a, b, c)` as just `a` and `b`, from which `form_from_bytes` recomputes `c`.
+pub fn form_to_bytes(a: &Integer, b: &Integer) -> Vec<u8> {
+  let mut bytes = Vec::new();
+  append_integer(&mut bytes, a);
+  append_integer(&mut bytes, b);
+  bytes
+}
+
+/// Decodes a form written by `form_to_bytes`, returning `None` unless `a > 0` and there is a
+/// `c = (b^2 - disc) / 4a`, i.e. the form has discriminant `disc`. Whether the form is reduced is
+/// left to the caller.
+pub fn form_from_bytes(mut bytes: &[u8], disc: &Integer) -> Option<(Integer, Integer, Integer)> {
+  let a = read_integer(&mut bytes)?;
+  let b = read_integer(&mut bytes)?;
+  if !bytes.is_empty() || a <= 0 {
+    return None;
+  }
+  let (c, rem) = (int(&b * &b) - disc).div_rem(int(&a * 4));
+  if rem != 0 {
+    return None;
+  }
+  Some((a, b, c))
+}
+
+/// Checks that `(a, b, c)` is a primitive, reduced form of discriminant `disc`, i.e. the unique
+/// representative of its class.
+pub fn validate_form(a: &Integer, b: &Integer, c: &Integer, disc: &Integer) -> Result<()> {
+  if *a <= 0 || int(b * b) - int(a * c) * 4 != *disc {
+    return Err(Error::InvalidElement);
+  }
+  // Reduced: `-a < b <= a <= c`, with `b >= 0` if `a = c`.
+  if *b <= -int(a) || b > a || a > c || (a == c && *b < 0) {
+    return Err(Error::InvalidElement);
+  }
+  if int(a.gcd_ref(b)).gcd(c) != 1 {
+    return Err(Error::InvalidElement);
+  }
+  Ok(())
+}
+
+impl<D: Discriminant> TypeRep for ClassGroup<D> {
+  type Rep = Integer;
+  fn rep() -> &'static Self::Rep {
+    D::discriminant()
+  }
+}
+
+// Moves `b` into `(-a, a]` without changing the class of the form.
+fn normalize<D: Discriminant>(x: &mut ClassGroupElem<D>) {
+  if -int(&x.a) < x.b && x.b <= x.a {
+    return;
+  }
+  let (r, _) = int(&x.a - &x.b).div_rem_floor(int(&x.a * 2));
+  // c' = a r^2 + b r + c, b' = b + 2 a r.
+  x.c += int(int(&x.a * &r) + &x.b) * &r;
+  x.b += int(&x.a * &r) * 2;
+}
+
+fn reduce<D: Discriminant>(mut x: ClassGroupElem<D>) -> ClassGroupElem<D> {
+  normalize(&mut x);
+  while x.a > x.c || (x.a == x.c && x.b < 0) {
+    std::mem::swap(&mut x.a, &mut x.c);
+    x.b = -x.b;
+    normalize(&mut x);
+  }
+  x
+}
+
+impl<D: Discriminant> Group for ClassGroup<D> {
+  type Elem = ClassGroupElem<D>;
+
+  // Composition as in Cohen, "A Course in Computational Algebraic Number Theory", Algorithm
+  // 5.4.7, followed by reduction.
+  fn op_(_: &Integer, x: &ClassGroupElem<D>, y: &ClassGroupElem<D>) -> ClassGroupElem<D> {
+    let (x, y) = if x.a > y.a { (y, x) } else { (x, y) };
+    let s = int(&x.b + &y.b) / 2;
+    let n = int(&y.b - &s);
+
+    let (d, y1) = if y.a.is_divisible(&x.a) {
+      (x.a.clone(), int(0))
+    } else {
+      let (d, u, _) = y.a.clone().gcd_cofactors(x.a.clone(), Integer::new());
+      (d, u)
+    };
+    let (d1, x2, y2) = if s.is_divisible(&d) {
+      (d, int(0), int(-1))
+    } else {
+      let (d1, x2, y2) = s.clone().gcd_cofactors(d, Integer::new());
+      (d1, x2, -y2)
+    };
+
+    let v1 = int(&x.a / &d1);
+    let v2 = int(&y.a / &d1);
+    let (_, r) = (y1 * y2 * n - x2 * &y.c).div_rem_euc(v1.clone());
+    let b = int(&y.b + int(&v2 * &r) * 2);
+    let c = int(int(&y.c * &d1) + int(&y.b + int(&v2 * &r)) * &r) / &v1;
+    reduce(form(v1 * v2, b, c))
+  }
+
+  fn id_(d: &Integer) -> ClassGroupElem<D> {
+    let c = int(1 - d) / 4;
+    form(int(1), int(1), c)
+  }
+
+  fn inv_(_: &Integer, x: &ClassGroupElem<D>) -> ClassGroupElem<D> {
+    reduce(form(x.a.clone(), int(-&x.b), x.c.clone()))
+  }
+
+  fn validate_(d: &Integer, x: &ClassGroupElem<D>) -> Result<()> {
+    validate_form(&x.a, &x.b, &x.c, d)
+  }
+}
+
+impl<D: Discriminant> UnknownOrderGroup for ClassGroup<D> {
+  fn unknown_order_elem_(d: &Integer) -> ClassGroupElem<D> {
+    // The form `(2, 1, (1 - d) / 8)`, the same element `classygroup` uses.
+    let c = int(1 - d) / 8;
+    reduce(form(int(2), int(1), c))
+  }
+
+  fn size_bits_(d: &Integer) -> Option<u32> {
+    Some(d.significant_bits())
+  }
+}
+
+/// Fails if `(a, b, c)` does not have the group discriminant.
+impl<D: Discriminant, A, B, C> ElemFrom<(A, B, C)> for ClassGroup<D>
+where
+  Integer: From<A>,
+  Integer: From<B>,
+  Integer: From<C>,
+{
+  fn try_elem(abc: (A, B, C)) -> Result<ClassGroupElem<D>> {
+    let x = form(int(abc.0), int(abc.1), int(abc.2));
+    let disc = int(&x.b * &x.b) - int(&x.a * &x.c) * 4;
+    if x.a <= 0 || disc != *Self::rep() {
+      return Err(Error::InvalidElement);
+    }
+    Ok(reduce(x))
+  }
+}
+
+impl<D: Discriminant> HashToGroup for ClassGroup<D> {
+  fn hash_to_group_(d: &Integer, t: &[u8]) -> ClassGroupElem<D> {
+    let (a, b, c) = hash_to_form(t, d);
+    reduce(form(a, b, c))
+  }
+}
+
+impl<D: Discriminant> ElemToBytes for ClassGroup<D> {
+  fn elem_to_bytes(x: &ClassGroupElem<D>) -> Vec<u8> {
+    form_to_bytes(&x.a, &x.b)
+  }
+
+  fn elem_from_bytes(bytes: &[u8]) -> Option<ClassGroupElem<D>> {
+    let (a, b, c) = form_from_bytes(bytes, Self::rep())?;
+    let x = form(a, b, c);
+    Self::validate(&x).ok().map(|_| x)
+  }
+}
+
+impl<D: Discriminant> HashPrime for ClassGroup<D> {
+  fn pick_prime_mpz(t: &[u8]) -> Integer {
+    hash_to_prime(t)
+  }
+
+  fn pick_prime_bytes(t: &[u8]) -> [u8; 32] {
+    hash_to_prime_bytes(t)
+  }
+
+  fn pick_prime_integer(t: &[u8]) -> Integer {
+    hash_to_prime(t)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  type ClassGroup = super::ClassGroup;
+
+  lazy_static! {
+    static ref DEPLOYMENT_DISCRIMINANT: Integer = generate_discriminant(b"deployment", 512);
+  }
+
+  #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+  enum Deployment {}
+
+  impl Discriminant for Deployment {
+    fn discriminant() -> &'static Integer {
+      &DEPLOYMENT_DISCRIMINANT
+    }
+  }
+
+  type DeploymentGroup = super::ClassGroup<Deployment>;
+
+  #[test]
+  fn test_generate_discriminant() {
+    for &bits in &[64, 256, 300] {
+      let d = generate_discriminant(b"seed", bits);
+      assert_eq!(d, generate_discriminant(b"seed", bits));
+      assert_eq!(d.significant_bits(), bits);
+      assert_eq!(int(-&d).mod_u(8), 7);
+      assert_ne!(int(-&d).is_probably_prime(50), IsPrime::No);
+    }
+    assert_ne!(generate_discriminant(b"seed", 256), generate_discriminant(b"seee", 256));
+  }
+
+  #[test]
+  fn test_seeded_discriminant() {
+    let d = DeploymentGroup::rep();
+    assert_eq!(d, &generate_discriminant(b"deployment", 512));
+    assert_eq!(DeploymentGroup::size_bits(), Some(512));
+    assert_eq!(ClassGroup::size_bits(), Some(CLASS_GROUP_DISCRIMINANT.significant_bits()));
+
+    let g = DeploymentGroup::unknown_order_elemnew();
+    assert_ne!(g.c, ClassGroup::unknown_order_elemnew().c);
+    let id = DeploymentGroup::idnew();
+    let mut x = g.clone();
+    for i in 2..50 {
+      x = DeploymentGroup::opnew(&x, &g);
+      assert_eq!(x, DeploymentGroup::exp(&g, &int(i)).unwrap());
+      assert_eq!(id, DeploymentGroup::opnew(&x, &DeploymentGroup::invnew(&x)));
+      let disc = int(&x.b * &x.b) - int(&x.a * &x.c) * 4;
+      assert_eq!(&disc, d);
+    }
+
+    // Forms of the default group do not belong to the seeded one.
+    let x = ClassGroup::hash_to_groupnew(b"default");
+    let foreign = form(x.a.clone(), x.b.clone(), x.c.clone());
+    assert_eq!(DeploymentGroup::validate(&foreign), Err(Error::InvalidElement));
+    assert_eq!(DeploymentGroup::elem_from_bytes(&ClassGroup::elem_to_bytes(&x)), None);
+  }
+
+  #[test]
+  fn test_seeded_group_laws() {
+    crate::groups::tests::check_id::<DeploymentGroup>();
+    crate::groups::tests::check_inv::<DeploymentGroup>();
+    crate::groups::tests::check_exp::<DeploymentGroup>();
+    crate::groups::tests::check_hash_to_group::<DeploymentGroup>();
+    crate::groups::tests::check_elem_bytes::<DeploymentGroup>();
+  }
+
+  #[test]
+  fn test_validate_form() {
+    // Discriminant -12 is not fundamental, so it has the non-primitive form `(2, 2, 2)`.
+    let d = int(-12);
+    assert_eq!(validate_form(&int(1), &int(0), &int(3), &d), Ok(()));
+    assert_eq!(validate_form(&int(2), &int(2), &int(2), &d), Err(Error::InvalidElement));
+    // `(1, 2, 4)` is equivalent to `(1, 0, 3)` but not reduced, and `(2, -2, 2)` has `b = -a`.
+    assert_eq!(validate_form(&int(1), &int(2), &int(4), &d), Err(Error::InvalidElement));
+    assert_eq!(validate_form(&int(2), &int(-2), &int(2), &d), Err(Error::InvalidElement));
+  }
+
+  #[test]
+  fn test_try_elem() {
+    let x = ClassGroup::hash_to_groupnew(b"try_elem");
+    assert_eq!(ClassGroup::try_elem((x.a.clone(), x.b.clone(), x.c.clone())), Ok(x.clone()));
+    assert_eq!(ClassGroup::try_elem((int(1), int(2), int(3))), Err(Error::InvalidElement));
+    let negated = (int(-&x.a), x.b.clone(), int(-&x.c));
+    assert_eq!(ClassGroup::try_elem(negated), Err(Error::InvalidElement));
+  }
+
+  #[test]
+  fn test_op_ground_truth() {
+    crate::groups::tests::check_op_ground_truth::<ClassGroup>(ClassGroup::elemnew);
+  }
+
+  #[test]
+  fn test_group_laws() {
+    crate::groups::tests::check_id::<ClassGroup>();
+    crate::groups::tests::check_inv::<ClassGroup>();
+    crate::groups::tests::check_exp::<ClassGroup>();
+    crate::groups::tests::check_exp_matches_binary::<ClassGroup>();
+    crate::groups::tests::check_hash_to_group::<ClassGroup>();
+    crate::groups::tests::check_multi_exp_general::<ClassGroup>();
+    crate::groups::tests::check_elem_bytes::<ClassGroup>();
+    crate::groups::tests::check_form_bytes::<ClassGroup>(|x| (x.a.clone(), x.b.clone(), x.c.clone()));
+    crate::groups::tests::check_validate::<ClassGroup>(|(a, b, c)| form(a, b, c));
+  }
+
+  #[test]
+  fn test_hash_to_form() {
+    for d in &[CLASS_GROUP_DISCRIMINANT.clone(), generate_discriminant(b"seed", 256)] {
+      for i in 0..10u8 {
+        let (a, b, c) = hash_to_form(&[i], d);
+        assert_eq!(int(&b * &b) - int(&a * &c) * 4, *d);
+        assert_ne!(a.is_probably_prime(50), IsPrime::No);
+      }
+    }
+  }
+
+  #[cfg(feature = "class_group_sti")]
+  #[test]
+  fn test_agrees_with_classygroup() {
+    use crate::groups::classgroupsti::ClassyGroup;
+    use rug::ops::Pow;
+    let g_sti = ClassyGroup::unknown_order_elemnew();
+    let g = ClassGroup::unknown_order_elemnew();
+    for i in 0..50 {
+      let e = int(3).pow(i) + i;
+      let x_sti = ClassyGroup::exp(&g_sti, &e).unwrap();
+      let x = ClassGroup::exp(&g, &e).unwrap();
+      assert_eq!(x.a.to_string(), x_sti.a.to_string());
+      assert_eq!(x.b.to_string(), x_sti.b.to_string());
+    }
+  }
+}
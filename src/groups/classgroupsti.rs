@@ -1,7 +1,10 @@
 
+use crate::error::{Error, Result};
 use crate::util::{TypeRep};
-use super::{Group, UnknownOrderGroup, ElemFrom, HashPrime};
+use super::classgroup::{form_from_bytes, form_to_bytes, hash_to_form, validate_form};
+use super::{Group, UnknownOrderGroup, ElemFrom, ElemToBytes, HashPrime, HashToGroup};
 use rug::Integer;
+use std::str::FromStr;
 
 pub use classygroup::{
     ClassElem,
@@ -11,7 +14,9 @@ pub use classygroup::{
     hash,
 };
 
-
+// `classygroup` composes forms under its own `CLASS_GROUP_DISCRIMINANT`, so this group cannot be
+// moved to another discriminant. Deployments that derive their own use
+// `classgroup::ClassGroup<D>`, which agrees with `ClassyGroup` for `D = DefaultDiscriminant`.
 impl TypeRep for ClassyGroup {
   type Rep = Mpz;
   fn rep() -> &'static Self::Rep {
@@ -30,7 +35,7 @@ impl Group for ClassyGroup {
 
   // Constructs the reduced element directly instead of using `Self::Elem()`.
   fn id_(_: &Mpz) -> ClassElem {
-    return ClassyGroup::id();
+    ClassyGroup::id()
   }
 
   // Constructs the inverse directly instead of using `Self::Elem()`.
@@ -41,23 +46,66 @@ impl Group for ClassyGroup {
   fn exp_(_: &Mpz, a: &ClassElem, n: &Integer) -> Option<ClassElem> {
     Some(ClassyGroup::pow(a, n))
   }
+
+  fn validate_(d: &Mpz, x: &ClassElem) -> Result<()> {
+    validate_form(&to_integer(&x.a), &to_integer(&x.b), &to_integer(&x.c), &to_integer(d))
+  }
 }
 
 impl UnknownOrderGroup for ClassyGroup {
   fn unknown_order_elem_(_: &Mpz) -> ClassElem {
     ClassyGroup::unknown_order_elem()
   }
+
+  fn size_bits_(d: &Mpz) -> Option<u32> {
+    Some(to_integer(d).significant_bits())
+  }
 }
 
-/// Panics if `(a, b, c)` cannot be reduced to a valid class element.
+/// Fails if `(a, b, c)` does not have the group discriminant.
 impl<A, B, C> ElemFrom<(A, B, C)> for ClassyGroup
 where
   Mpz: From<A>,
   Mpz: From<B>,
   Mpz: From<C>,
 {
-  fn elemnew(abc: (A, B, C)) -> ClassElem {
-    ClassyGroup::elem((Mpz::from(abc.0), Mpz::from(abc.1), Mpz::from(abc.2)))
+  fn try_elem(abc: (A, B, C)) -> Result<ClassElem> {
+    let (a, b, c) = (Mpz::from(abc.0), Mpz::from(abc.1), Mpz::from(abc.2));
+    // `ClassyGroup::elem` panics on forms of another discriminant, so check it first.
+    let (a_int, b_int, c_int) = (to_integer(&a), to_integer(&b), to_integer(&c));
+    let disc = Integer::from(&b_int * &b_int) - Integer::from(&a_int * &c_int) * 4;
+    if a_int <= 0 || disc != to_integer(Self::rep()) {
+      return Err(Error::InvalidElement);
+    }
+    Ok(ClassyGroup::elem((a, b, c)))
+  }
+}
+
+// `Mpz` and `Integer` only share their decimal representation.
+fn to_integer(x: &Mpz) -> Integer {
+  Integer::from_str(&x.to_string()).unwrap()
+}
+
+fn to_mpz(x: &Integer) -> Mpz {
+  Mpz::from_str(&x.to_string()).unwrap()
+}
+
+impl HashToGroup for ClassyGroup {
+  fn hash_to_group_(d: &Mpz, t: &[u8]) -> ClassElem {
+    let (a, b, c) = hash_to_form(t, &to_integer(d));
+    ClassyGroup::elemnew((to_mpz(&a), to_mpz(&b), to_mpz(&c)))
+  }
+}
+
+impl ElemToBytes for ClassyGroup {
+  fn elem_to_bytes(x: &ClassElem) -> Vec<u8> {
+    form_to_bytes(&to_integer(&x.a), &to_integer(&x.b))
+  }
+
+  fn elem_from_bytes(bytes: &[u8]) -> Option<ClassElem> {
+    let (a, b, c) = form_from_bytes(bytes, &to_integer(Self::rep()))?;
+    let x = ClassElem { a: to_mpz(&a), b: to_mpz(&b), c: to_mpz(&c) };
+    Self::validate(&x).ok().map(|_| x)
   }
 }
 
@@ -99,6 +147,14 @@ mod tests {
         let _ = ClassyGroup::elemnew((Mpz::from(1), Mpz::from(2), Mpz::from(3)));
     }
 
+    #[test]
+    fn test_try_elem() {
+        let bad = ClassyGroup::try_elem((Mpz::from(1), Mpz::from(2), Mpz::from(3)));
+        assert_eq!(bad, Err(Error::InvalidElement));
+        let x = ClassyGroup::unknown_order_elem();
+        assert_eq!(ClassyGroup::try_elem((x.a.clone(), x.b.clone(), x.c.clone())), Ok(x));
+    }
+
     #[test]
     fn test_elem_from() {
         let a1 = Mpz::from_str("16").unwrap();
@@ -170,7 +226,7 @@ mod tests {
       07264365691511785213717281118305284397086833770388796703509"
     );
 
-        assert_eq!(ClassyGroup::op_(&ClassyGroup::rep(), &a, &b), ground_truth);
+        assert_eq!(ClassyGroup::op_(ClassyGroup::rep(), &a, &b), ground_truth);
     }
 
     #[test]
@@ -238,12 +294,12 @@ mod tests {
             let mut curr_prod = ClassyGroup::idnew();
             for elem in &gs {
                 if elem != g_elem {
-                    curr_prod = ClassyGroup::opnew(&curr_prod, &elem);
+                    curr_prod = ClassyGroup::opnew(&curr_prod, elem);
                     
                 }
             }
-            assert_eq!(ClassyGroup::idnew(), ClassyGroup::opnew(&g_inv, &g_elem));
-            assert_eq!(curr_prod, ClassyGroup::opnew(&g_inv, &g_star));
+            assert_eq!(ClassyGroup::idnew(), ClassyGroup::opnew(g_inv, g_elem));
+            assert_eq!(curr_prod, ClassyGroup::opnew(g_inv, &g_star));
         }
     }
 
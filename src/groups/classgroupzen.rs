@@ -0,0 +1,159 @@
+//! Class group backed by ZenGo's `class_group` crate (https://github.com/ZenGo-X/class), which
+//! composes binary quadratic forms with PARI.
+use super::classgroup::{form_from_bytes, form_to_bytes, hash_to_form, validate_form, CLASS_GROUP_DISCRIMINANT};
+use super::{ElemFrom, ElemToBytes, Group, HashPrime, HashToGroup, UnknownOrderGroup};
+use crate::error::{Error, Result};
+use crate::hash::{hash_to_prime, hash_to_prime_bytes};
+use crate::util::{int, TypeRep};
+use class_group::BinaryQF;
+use rug::Integer;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+/// Class group of binary quadratic forms whose group law is delegated to ZenGo's `BinaryQF`.
+///
+/// It works over the same discriminant as `classgroupsti::ClassyGroup`, so the two backends
+/// compute in the same group and their results can be cross-checked.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ZenClassGroup {}
+
+/// A reduced form `(a, b, c)` with `b^2 - 4ac` equal to the group discriminant.
+///
+/// The coefficients are kept as `rug` integers rather than as a `BinaryQF`, so that elements
+/// are hashable and compare equal exactly when they are the same reduced form.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ZenClassElem {
+  pub a: Integer,
+  pub b: Integer,
+  pub c: Integer,
+}
+
+// ZenGo's `BigInt` parses and prints decimal strings, which is all the conversions rely on.
+fn to_big<B: FromStr>(n: &Integer) -> B
+where
+  B::Err: Debug,
+{
+  B::from_str(&n.to_string()).unwrap()
+}
+
+fn from_big<B: ToString>(n: &B) -> Integer {
+  Integer::from_str(&n.to_string()).unwrap()
+}
+
+fn to_qf(x: &ZenClassElem) -> BinaryQF {
+  BinaryQF {
+    a: to_big(&x.a),
+    b: to_big(&x.b),
+    c: to_big(&x.c),
+  }
+}
+
+fn from_qf(qf: &BinaryQF) -> ZenClassElem {
+  ZenClassElem {
+    a: from_big(&qf.a),
+    b: from_big(&qf.b),
+    c: from_big(&qf.c),
+  }
+}
+
+impl TypeRep for ZenClassGroup {
+  type Rep = Integer;
+  fn rep() -> &'static Self::Rep {
+    &CLASS_GROUP_DISCRIMINANT
+  }
+}
+
+impl Group for ZenClassGroup {
+  type Elem = ZenClassElem;
+
+  fn op_(_: &Integer, x: &ZenClassElem, y: &ZenClassElem) -> ZenClassElem {
+    from_qf(&to_qf(x).compose(&to_qf(y)).reduce())
+  }
+
+  fn id_(d: &Integer) -> ZenClassElem {
+    let c = int(1 - d) / 4;
+    ZenClassElem { a: int(1), b: int(1), c }
+  }
+
+  fn inv_(_: &Integer, x: &ZenClassElem) -> ZenClassElem {
+    from_qf(&to_qf(x).inverse().reduce())
+  }
+
+  fn exp_(_: &Integer, a: &ZenClassElem, n: &Integer) -> Option<ZenClassElem> {
+    let (base, n) = if *n < 0 {
+      (Self::invnew(a), int(-n))
+    } else {
+      (a.clone(), n.clone())
+    };
+    Some(from_qf(&to_qf(&base).exp(&to_big(&n)).reduce()))
+  }
+
+  fn validate_(d: &Integer, x: &ZenClassElem) -> Result<()> {
+    validate_form(&x.a, &x.b, &x.c, d)
+  }
+}
+
+impl UnknownOrderGroup for ZenClassGroup {
+  fn unknown_order_elem_(d: &Integer) -> ZenClassElem {
+    // The form `(2, 1, (1 - d) / 8)`, the same element `classygroup` uses.
+    let c = int(1 - d) / 8;
+    Self::elemnew((int(2), int(1), c))
+  }
+
+  fn size_bits_(d: &Integer) -> Option<u32> {
+    Some(d.significant_bits())
+  }
+}
+
+/// Fails if `(a, b, c)` does not have the group discriminant.
+impl<A, B, C> ElemFrom<(A, B, C)> for ZenClassGroup
+where
+  Integer: From<A>,
+  Integer: From<B>,
+  Integer: From<C>,
+{
+  fn try_elem(abc: (A, B, C)) -> Result<ZenClassElem> {
+    let x = ZenClassElem {
+      a: int(abc.0),
+      b: int(abc.1),
+      c: int(abc.2),
+    };
+    let disc = int(&x.b * &x.b) - int(&x.a * &x.c) * 4;
+    if x.a <= 0 || disc != *Self::rep() {
+      return Err(Error::InvalidElement);
+    }
+    Ok(from_qf(&to_qf(&x).reduce()))
+  }
+}
+
+impl HashToGroup for ZenClassGroup {
+  fn hash_to_group_(d: &Integer, t: &[u8]) -> ZenClassElem {
+    Self::elemnew(hash_to_form(t, d))
+  }
+}
+
+impl ElemToBytes for ZenClassGroup {
+  fn elem_to_bytes(x: &ZenClassElem) -> Vec<u8> {
+    form_to_bytes(&x.a, &x.b)
+  }
+
+  fn elem_from_bytes(bytes: &[u8]) -> Option<ZenClassElem> {
+    let (a, b, c) = form_from_bytes(bytes, Self::rep())?;
+    let x = ZenClassElem { a, b, c };
+    Self::validate(&x).ok().map(|_| x)
+  }
+}
+
+impl HashPrime for ZenClassGroup {
+  fn pick_prime_mpz(t: &[u8]) -> Integer {
+    hash_to_prime(t)
+  }
+
+  fn pick_prime_bytes(t: &[u8]) -> [u8; 32] {
+    hash_to_prime_bytes(t)
+  }
+
+  fn pick_prime_integer(t: &[u8]) -> Integer {
+    hash_to_prime(t)
+  }
+}
@@ -0,0 +1,304 @@
+//! Fixed-base exponentiation with precomputed powers of the base.
+use super::{op_opt, Group};
+#[cfg(feature = "serde")]
+use super::ElemToBytes;
+#[cfg(feature = "serde")]
+use crate::error::Error;
+#[cfg(feature = "serde")]
+use crate::serialization::{
+  deserialize_elem, deserialize_elems, deserialize_versioned, serialize_elem, serialize_elems, versioned, Versioned,
+};
+use crate::util::int;
+use rug::Integer;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+// Bucket counts beyond `2^16` would cost more memory than the table they are meant to save.
+const MAX_WINDOW: u32 = 16;
+
+/// The powers `base^(2^(window * i))` of a fixed base, which turn every exponentiation of that
+/// base into about `max_bits / window + 2^(window + 1)` group operations and no squarings.
+///
+/// An exponent `e = sum_i d_i 2^(window * i)` with digits `d_i < 2^window` is evaluated as
+/// `prod_d (prod_{i : d_i = d} powers[i])^d`, where the outer product is accumulated bucket by
+/// bucket as in Pippenger's method. Exponents wider than `max_bits()` fall back to `G::exp`.
+///
+/// With the `parallel` feature, the windows of an exponent are split across threads. This is
+/// what makes fixed bases worth parallelizing: for an arbitrary base, the chain of squarings
+/// is inherently sequential.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
+pub struct FixedBaseTable<G: Group> {
+  window: u32,
+  #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
+  base: G::Elem,
+  #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elems::<G, _>", deserialize_with = "deserialize_elems::<G, _>"))]
+  powers: Vec<G::Elem>,
+}
+
+impl<G: Group> FixedBaseTable<G> {
+  /// Precomputes the table of `base` for exponents of up to `max_bits` bits, storing at most
+  /// `max_elems` group elements.
+  ///
+  /// The window is the cheapest one whose table fits into `max_elems`; if even the widest window
+  /// needs more, the table only covers the low `max_elems * 16` bits.
+  pub fn new(base: &G::Elem, max_bits: u32, max_elems: usize) -> FixedBaseTable<G> {
+    assert!(max_elems > 0, "a fixed-base table needs room for at least one element");
+    let windows = |w: u32| max_bits.max(1).div_ceil(w) as usize;
+    let window = (1..=MAX_WINDOW)
+      .filter(|&w| windows(w) <= max_elems)
+      .min_by_key(|&w| windows(w) + (1 << (w + 1)))
+      .unwrap_or(MAX_WINDOW);
+
+    let mut powers = Vec::with_capacity(windows(window).min(max_elems));
+    let mut power = base.clone();
+    for _ in 0..windows(window).min(max_elems) {
+      let next = next_power::<G>(&power, window);
+      powers.push(power);
+      power = next;
+    }
+    FixedBaseTable { window, base: base.clone(), powers }
+  }
+
+  /// The base this table raises to powers.
+  pub fn base(&self) -> &G::Elem {
+    &self.base
+  }
+
+  /// The number of bits of the widest exponent the table covers.
+  pub fn max_bits(&self) -> u32 {
+    self.window * self.powers.len() as u32
+  }
+
+  /// Computes `base^n`.
+  pub fn exp(&self, n: &Integer) -> G::Elem {
+    if n.significant_bits() > self.max_bits() {
+      return G::exp(&self.base, n).unwrap();
+    }
+    if *n < 0 {
+      return G::invnew(&self.exp(&int(-n)));
+    }
+
+    #[cfg(feature = "parallel")]
+    {
+      use rayon::prelude::*;
+      // Every chunk sums its own `2^window` buckets, so chunks have at least that many windows.
+      let len = self.powers.len();
+      let chunk = len.div_ceil(rayon::current_num_threads()).max(1 << self.window);
+      if len > chunk {
+        return (0..len)
+          .into_par_iter()
+          .step_by(chunk)
+          .filter_map(|start| self.exp_windows(n, start, (start + chunk).min(len)))
+          .reduce(G::idnew, |a, b| G::opnew(&a, &b));
+      }
+    }
+    self.exp_windows(n, 0, self.powers.len()).unwrap_or_else(G::idnew)
+  }
+
+  // The product of `powers[i]^d_i` over the windows `start <= i < end`, or `None` if all of
+  // their digits are zero.
+  fn exp_windows(&self, n: &Integer, start: usize, end: usize) -> Option<G::Elem> {
+    let mut buckets: Vec<Option<G::Elem>> = vec![None; (1 << self.window) - 1];
+    for (i, power) in self.powers.iter().enumerate().take(end).skip(start) {
+      let digit = super::window(n, i as u32 * self.window, self.window);
+      if digit != 0 {
+        buckets[digit - 1] = op_opt::<G>(buckets[digit - 1].take(), power);
+      }
+    }
+    let mut running = None;
+    let mut acc = None;
+    for bucket in buckets.iter().rev() {
+      if let Some(bucket) = bucket {
+        running = op_opt::<G>(running, bucket);
+      }
+      if let Some(running) = &running {
+        acc = op_opt::<G>(acc, running);
+      }
+    }
+    acc
+  }
+
+  // Checks that a deserialized table has a shape `new` could have built, i.e. one `exp` can
+  // afford: a window of at most `MAX_WINDOW` bits, and `powers` starting at `base`.
+  #[cfg(feature = "serde")]
+  fn is_well_formed(&self) -> bool {
+    (1..=MAX_WINDOW).contains(&self.window)
+      && self.powers.first() == Some(&self.base)
+      && (self.powers.len() as u64) * u64::from(self.window) <= u64::from(u32::MAX)
+  }
+
+  // Checks that a deserialized table is one `new` could have built, which re-derives all powers.
+  #[cfg(feature = "serde")]
+  fn is_consistent(&self) -> bool {
+    self.is_well_formed() && self.powers.windows(2).all(|w| next_power::<G>(&w[0], self.window) == w[1])
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<G: ElemToBytes> FixedBaseTable<G> {
+  /// Deserializes a table written by `Serialize`, without checking that its powers are those of
+  /// its base. Unlike `Deserialize`, which recomputes every power, this costs no group
+  /// operations, so it is what makes persisting a table pay off.
+  ///
+  /// Only use it for tables from a trusted source, such as ones the application wrote itself:
+  /// tampered powers make `exp` compute wrong results. It can serve as
+  /// `#[serde(deserialize_with = "FixedBaseTable::deserialize_trusted")]`.
+  pub fn deserialize_trusted<'de, D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    deserialize_versioned::<Trusted<G>, D>(d).map(|trusted| trusted.0)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<G: ElemToBytes> Versioned for FixedBaseTable<G> {
+  fn serialize_body<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    FixedBaseTable::serialize(self, s)
+  }
+
+  /// Deserializes a table, failing unless it is consistent, as a tampered table would make
+  /// `exp` panic, allocate `2^window` buckets, or silently compute wrong powers. Checking the
+  /// powers re-derives the whole table, which costs as much as `new`; see `deserialize_trusted`.
+  fn deserialize_body<'de, D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    let table = FixedBaseTable::deserialize(d)?;
+    if !table.is_consistent() {
+      return Err(inconsistent::<D::Error>());
+    }
+    Ok(table)
+  }
+}
+
+// A table read by `deserialize_trusted`, whose shape is checked but not its powers.
+#[cfg(feature = "serde")]
+struct Trusted<G: Group>(FixedBaseTable<G>);
+
+#[cfg(feature = "serde")]
+impl<G: ElemToBytes> Versioned for Trusted<G> {
+  fn serialize_body<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    FixedBaseTable::serialize(&self.0, s)
+  }
+
+  fn deserialize_body<'de, D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    let table = FixedBaseTable::deserialize(d)?;
+    if !table.is_well_formed() {
+      return Err(inconsistent::<D::Error>());
+    }
+    Ok(Trusted(table))
+  }
+}
+
+// The error for a table `new` could not have built.
+#[cfg(feature = "serde")]
+fn inconsistent<E: serde::de::Error>() -> E {
+  E::custom(Error::Deserialization("inconsistent fixed-base table".into()))
+}
+
+#[cfg(feature = "serde")]
+versioned!(@serde FixedBaseTable<G> where G: ElemToBytes);
+
+// Raises `x` to the power `2^window`.
+fn next_power<G: Group>(x: &G::Elem, window: u32) -> G::Elem {
+  (0..window).fold(x.clone(), |x, _| G::opnew(&x, &x))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  type ClassGroup = crate::groups::classgroup::ClassGroup;
+  use crate::groups::{HashToGroup, UnknownOrderGroup};
+  use rand::Rng;
+  use rug::integer::Order;
+
+  fn random_exp(bytes: usize) -> Integer {
+    let digits: Vec<u8> = (0..bytes).map(|_| rand::thread_rng().gen()).collect();
+    Integer::from_digits(&digits, Order::Msf)
+  }
+
+  #[test]
+  fn test_exp() {
+    let g = ClassGroup::hash_to_groupnew(b"fixed base");
+    let table = FixedBaseTable::<ClassGroup>::new(&g, 1000, 1000);
+    assert!(table.max_bits() >= 1000);
+    assert_eq!(table.base(), &g);
+    for n in &[int(0), int(1), int(2), int(-1), int(1) << 999, random_exp(125), -random_exp(100)] {
+      assert_eq!(table.exp(n), ClassGroup::exp(&g, n).unwrap());
+    }
+  }
+
+  #[test]
+  fn test_memory_cap() {
+    let g = ClassGroup::unknown_order_elemnew();
+    for &(max_bits, max_elems) in &[(1000, 100), (1000, 64), (1000, 10), (1, 1)] {
+      let table = FixedBaseTable::<ClassGroup>::new(&g, max_bits, max_elems);
+      assert!(table.powers.len() <= max_elems);
+      let n = random_exp(125);
+      assert_eq!(table.exp(&n), ClassGroup::exp(&g, &n).unwrap());
+    }
+    // Exponents beyond the covered bits fall back to `exp`.
+    let table = FixedBaseTable::<ClassGroup>::new(&g, 256, 4);
+    assert!(table.max_bits() < 256);
+    let n = random_exp(32);
+    assert_eq!(table.exp(&n), ClassGroup::exp(&g, &n).unwrap());
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_parallel() {
+    let g = ClassGroup::hash_to_groupnew(b"fixed base");
+    let table = FixedBaseTable::<ClassGroup>::new(&g, 2000, 1000);
+    for n in &[random_exp(250), int(1) << 1999, int(0)] {
+      let sequential = table.exp_windows(n, 0, table.powers.len()).unwrap_or_else(ClassGroup::idnew);
+      assert_eq!(crate::groups::tests::in_pool(|| table.exp(n)), sequential);
+      assert_eq!(sequential, ClassGroup::exp(&g, n).unwrap());
+    }
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde() {
+    let g = ClassGroup::hash_to_groupnew(b"fixed base");
+    let table = FixedBaseTable::<ClassGroup>::new(&g, 500, 100);
+    let bytes = bincode::serialize(&table).unwrap();
+    let table_2: FixedBaseTable<ClassGroup> = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(table_2, table);
+    assert!(bincode::deserialize::<FixedBaseTable<ClassGroup>>(&bytes[..bytes.len() - 1]).is_err());
+
+    let tampered = |f: &dyn Fn(&mut FixedBaseTable<ClassGroup>)| {
+      let mut table = table.clone();
+      f(&mut table);
+      bincode::deserialize::<FixedBaseTable<ClassGroup>>(&bincode::serialize(&table).unwrap())
+    };
+    assert!(tampered(&|_| ()).is_ok());
+    assert!(tampered(&|t| t.window = 0).is_err());
+    assert!(tampered(&|t| t.window = 64).is_err());
+    assert!(tampered(&|t| t.window = 30).is_err());
+    assert!(tampered(&|t| t.powers.clear()).is_err());
+    assert!(tampered(&|t| t.powers[1] = g.clone()).is_err());
+    assert!(tampered(&|t| t.base = ClassGroup::unknown_order_elemnew()).is_err());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_deserialize_trusted() {
+    #[derive(serde::Deserialize)]
+    struct Stored(#[serde(deserialize_with = "FixedBaseTable::deserialize_trusted")] FixedBaseTable<ClassGroup>);
+
+    let g = ClassGroup::hash_to_groupnew(b"fixed base");
+    let table = FixedBaseTable::<ClassGroup>::new(&g, 500, 100);
+    let read = |table: &FixedBaseTable<ClassGroup>| {
+      bincode::deserialize::<Stored>(&bincode::serialize(table).unwrap()).map(|stored| stored.0)
+    };
+    assert_eq!(read(&table).unwrap(), table);
+
+    // The powers are taken as they are, but not a shape `exp` cannot handle.
+    let mut wrong_power = table.clone();
+    wrong_power.powers[1] = g.clone();
+    assert_eq!(read(&wrong_power).unwrap(), wrong_power);
+    let mut wide = table.clone();
+    wide.window = 30;
+    assert!(read(&wide).is_err());
+    let mut other_base = table;
+    other_base.base = ClassGroup::unknown_order_elemnew();
+    assert!(read(&other_base).is_err());
+  }
+}
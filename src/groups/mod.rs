@@ -1,11 +1,20 @@
 //! Implementations for different mathematical groups, each of which satisfies our
 //! `UnknownOrderGroup` trait. 
-use crate::util::{int, TypeRep};
+use crate::error::{Error, Result};
+use crate::util::{int, join, TypeRep};
 use rug::Integer;
-use std::hash::Hash;
+use std::cmp::Ordering;
 use std::fmt::Debug;
+use std::hash::Hash;
 
+pub mod classgroup;
+#[cfg(feature = "class_group_sti")]
 pub mod classgroupsti;
+#[cfg(feature = "class_group_zen")]
+pub mod classgroupzen;
+pub mod fixed_base;
+#[cfg(feature = "rsa_group")]
+pub mod rsa;
 
 /// [Follow the idea from `https://github.com/cambrian/accumulator/src/group/mod.rs`]
 /// A mathematical group.
@@ -22,7 +31,7 @@ pub mod classgroupsti;
 /// Clients of this trait need to implement functions of the form `*_`, which take in `TypeRep`
 /// data as a parameter. Consumers use functions without the underscore: `id`, `op`, `exp`, and
 /// `inv`.
-
+//
 // The other traits are only required here because Rust can't figure out how to do stuff with an
 // `Accumulator<G>` even though it's just a wrapped `G::Elem`. If possible we'd remove them.
 pub trait Group: Clone + Debug + Eq + Hash + TypeRep + Send + Sync {
@@ -40,27 +49,52 @@ pub trait Group: Clone + Debug + Eq + Hash + TypeRep + Send + Sync {
   /// A group-specific wrapper for `op`.
   fn op_(rep: &Self::Rep, a: &Self::Elem, b: &Self::Elem) -> Self::Elem;
 
+  /// A group-specific wrapper for `validate`, although it comes with a default implementation
+  /// which only checks that `x` is left as it is by the group operation with the identity.
+  ///
+  /// That rejects representations the operation would normalize, but not values outside the
+  /// group, so implementations for groups whose elements come from untrusted sources should
+  /// check membership as well (e.g. the discriminant of a class group form).
+  fn validate_(rep: &Self::Rep, x: &Self::Elem) -> Result<()> {
+    if Self::op_(rep, &Self::id_(rep), x) != *x {
+      return Err(Error::InvalidElement);
+    }
+    Ok(())
+  }
+
   /// A group-specific wrapper for `exp`, although it comes with a default implementation via
-  /// repeated squaring.
+  /// width-`w` NAF, which trades squarings for multiplications by cheap inverses.
   ///
   /// Specific implementations may provide more performant specializations as needed (e.g.
   /// Montgomery multiplication for RSA groups).
-  fn exp_(_rep: &Self::Rep, a: &Self::Elem, n: &Integer) -> Option<Self::Elem> {
-    let (mut val, mut a, mut n) = {
-      if *n < int(0) {
-        (Self::idnew(), Self::invnew(a), int(-n))
-      } else {
-        (Self::idnew(), a.clone(), n.clone())
-      }
+  fn exp_(rep: &Self::Rep, a: &Self::Elem, n: &Integer) -> Option<Self::Elem> {
+    let w = match n.significant_bits() {
+      0..=32 => 2,
+      33..=256 => 4,
+      _ => 5,
     };
-    while n > int(0) {
-      if n.is_odd() {
-        val = Self::opnew(&val, &a);
-      }
-      a = Self::opnew(&a, &a);
-      n >>= 1;
+    // `odd[i] = a^(2i + 1)` and `odd_inv[i] = a^-(2i + 1)`, for the digits in `(-2^(w-1), 2^(w-1))`.
+    let a_squared = Self::op_(rep, a, a);
+    let mut odd = vec![a.clone()];
+    for i in 1..1 << (w - 2) {
+      odd.push(Self::op_(rep, &odd[i - 1], &a_squared));
     }
-    Some(val)
+    let odd_inv: Vec<Self::Elem> = odd.iter().map(|x| Self::inv_(rep, x)).collect();
+
+    let mut val: Option<Self::Elem> = None;
+    for digit in wnaf(n, w).iter().rev() {
+      val = val.map(|val| Self::op_(rep, &val, &val));
+      let x = match digit.cmp(&0) {
+        Ordering::Greater => &odd[(*digit as usize - 1) / 2],
+        Ordering::Less => &odd_inv[(-*digit as usize - 1) / 2],
+        Ordering::Equal => continue,
+      };
+      val = Some(match val {
+        Some(val) => Self::op_(rep, &val, x),
+        None => x.clone(),
+      });
+    }
+    Some(val.unwrap_or_else(|| Self::id_(rep)))
   }
 
   /// A group-specific wrapper for `inv`.
@@ -81,6 +115,9 @@ pub trait Group: Clone + Debug + Eq + Hash + TypeRep + Send + Sync {
   }
 
   /// Applies the group operation to `a` and itself `n` times and returns the result.
+  ///
+  /// Returns `None` only if `n` is negative and `a` has no inverse. Every element built with
+  /// `elemnew` or accepted by `validate` has one, so for those the result can be unwrapped.
   fn exp(a: &Self::Elem, n: &Integer) -> Option<Self::Elem> {
     Self::exp_(Self::rep(), a, n)
   }
@@ -89,6 +126,35 @@ pub trait Group: Clone + Debug + Eq + Hash + TypeRep + Send + Sync {
   fn invnew(a: &Self::Elem) -> Self::Elem {
     Self::inv_(Self::rep(), a)
   }
+
+  /// Checks that `x` is an element of the group in its canonical representation, failing with
+  /// `Error::InvalidElement` otherwise. Elements from untrusted sources have to pass this before
+  /// they are used.
+  fn validate(x: &Self::Elem) -> Result<()> {
+    Self::validate_(Self::rep(), x)
+  }
+}
+
+/// The width-`w` non-adjacent form of `n`, least significant digit first: `n = sum_i d_i 2^i` with
+/// every non-zero `d_i` odd and below `2^(w-1)` in absolute value, and at most one of any `w`
+/// consecutive digits non-zero.
+fn wnaf(n: &Integer, w: u32) -> Vec<i32> {
+  let sign = if *n < 0 { -1 } else { 1 };
+  let mut n = int(n.abs_ref());
+  let mut digits = Vec::with_capacity(n.significant_bits() as usize + 1);
+  while n != 0 {
+    let mut digit = 0;
+    if n.is_odd() {
+      digit = n.mod_u(1 << w) as i32;
+      if digit >= 1 << (w - 1) {
+        digit -= 1 << w;
+      }
+      n -= digit;
+    }
+    digits.push(sign * digit);
+    n >>= 1;
+  }
+  digits
 }
 
 /// A group containing elements of unknown order.
@@ -103,12 +169,29 @@ pub trait UnknownOrderGroup: Group {
 
   /// A group-specific wrapper for `unknown_order_elem`.
   fn unknown_order_elem_(rep: &Self::Rep) -> Self::Elem;
+
+  /// Returns the bit length of the modulus or discriminant the group is defined by, which is
+  /// what the `lambda` of `PolyComm::setup` refers to, or `None` if the group does not say.
+  fn size_bits() -> Option<u32> {
+    Self::size_bits_(Self::rep())
+  }
+
+  /// A group-specific wrapper for `size_bits`.
+  fn size_bits_(_rep: &Self::Rep) -> Option<u32> {
+    None
+  }
 }
 
 /// Like `From<T>`, but implemented on the `Group` instead of the element type.
 pub trait ElemFrom<T>: Group {
-  /// Returns a group element from an initial value.
-  fn elemnew(val: T) -> Self::Elem;
+  /// Returns a group element from an initial value, or `Error::InvalidElement` if the value does
+  /// not describe one.
+  fn try_elem(val: T) -> Result<Self::Elem>;
+
+  /// Returns a group element from an initial value, panicking if the value does not describe one.
+  fn elemnew(val: T) -> Self::Elem {
+    Self::try_elem(val).expect("invalid group element")
+  }
 }
 
 pub trait HashPrime: Group {
@@ -117,11 +200,40 @@ pub trait HashPrime: Group {
   fn pick_prime_bytes(t: &[u8]) -> [u8; 32];
 }
 
+/// A group whose elements can be derived from arbitrary bytes, such that nobody knows a
+/// discrete log relation between the elements obtained from different inputs.
+pub trait HashToGroup: Group {
+  /// Deterministically maps `t` to a group element.
+  fn hash_to_groupnew(t: &[u8]) -> Self::Elem {
+    Self::hash_to_group_(Self::rep(), t)
+  }
+
+  /// A group-specific wrapper for `hash_to_group`.
+  fn hash_to_group_(rep: &Self::Rep, t: &[u8]) -> Self::Elem;
+}
+
+/// A canonical byte encoding of group elements, independent of the backend's own element type.
+pub trait ElemToBytes: Group {
+  /// Encodes `x`; equal elements have equal encodings.
+  fn elem_to_bytes(x: &Self::Elem) -> Vec<u8>;
+
+  /// Decodes an element, returning `None` unless `bytes` is exactly the encoding of an element.
+  fn elem_from_bytes(bytes: &[u8]) -> Option<Self::Elem>;
+}
+
 /// Computes the product of `alpha_i ^ (p(x) / x_i)`, where `i` is an index into the `alphas` and
 /// `x` arrays, and `p(x)` is the product of all `x_i`. See BBF (page 11).
+///
+/// Panics where `try_multi_exp` fails.
 pub fn multi_exp<G: Group>(alphas: &[G::Elem], x: &[Integer]) -> G::Elem {
+  try_multi_exp::<G>(alphas, x).expect("an intermediate product has no inverse")
+}
+
+/// Same as `multi_exp`, but fails with `Error::InvalidElement` if some `x_i` is negative and an
+/// intermediate product has no inverse.
+pub fn try_multi_exp<G: Group>(alphas: &[G::Elem], x: &[Integer]) -> Result<G::Elem> {
   if alphas.len() == 1 {
-    return alphas[0].clone();
+    return Ok(alphas[0].clone());
   }
 
   let n_half = alphas.len() / 2;
@@ -131,7 +243,139 @@ pub fn multi_exp<G: Group>(alphas: &[G::Elem], x: &[Integer]) -> G::Elem {
   let x_r = &x[n_half..];
   let x_star_l = x_l.iter().product();
   let x_star_r = x_r.iter().product();
-  let l = multi_exp::<G>(alpha_l, x_l);
-  let r = multi_exp::<G>(alpha_r, x_r);
-  G::opnew(&G::exp(&l, &x_star_r).unwrap(), &G::exp(&r, &x_star_l).unwrap())
+  let (l, r) = join(|| try_multi_exp::<G>(alpha_l, x_l), || try_multi_exp::<G>(alpha_r, x_r));
+  let (l, r) = (l?, r?);
+  let (l, r) = join(|| G::exp(&l, &x_star_r), || G::exp(&r, &x_star_l));
+  Ok(G::opnew(&l.ok_or(Error::InvalidElement)?, &r.ok_or(Error::InvalidElement)?))
 }
+
+/// Computes `prod_i bases[i]^exps[i]` for arbitrary exponents, which may be negative.
+///
+/// Uses Straus' interleaved windowed method for few bases and Pippenger's bucket method for many,
+/// so all bases share one chain of squarings. With the `parallel` feature, the bases are split
+/// into one chunk per thread first. Panics if `bases` and `exps` have different lengths.
+pub fn multi_exp_general<G: Group>(bases: &[G::Elem], exps: &[Integer]) -> G::Elem {
+  assert_eq!(bases.len(), exps.len(), "multi_exp_general needs one exponent per base");
+  // Fold the signs into the bases so that only non-negative exponents are left.
+  let (bases, exps): (Vec<G::Elem>, Vec<Integer>) = bases
+    .iter()
+    .zip(exps)
+    .filter(|(_, e)| **e != 0)
+    .map(|(x, e)| if *e < 0 { (G::invnew(x), int(-e)) } else { (x.clone(), e.clone()) })
+    .unzip();
+
+  #[cfg(feature = "parallel")]
+  {
+    use rayon::prelude::*;
+    // Every chunk repeats the squarings, so chunks should not get much smaller than that.
+    let chunk = bases.len().div_ceil(rayon::current_num_threads()).max(PARALLEL_CHUNK);
+    if bases.len() > chunk {
+      return bases
+        .par_chunks(chunk)
+        .zip(exps.par_chunks(chunk))
+        .map(|(bases, exps)| multi_exp_unsigned::<G>(bases, exps))
+        .reduce(G::idnew, |a, b| G::opnew(&a, &b));
+    }
+  }
+  multi_exp_unsigned::<G>(&bases, &exps)
+}
+
+// The smallest number of bases worth a thread of its own in `multi_exp_general`.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK: usize = 8;
+
+pub(crate) fn multi_exp_unsigned<G: Group>(bases: &[G::Elem], exps: &[Integer]) -> G::Elem {
+  let bits = exps.iter().map(Integer::significant_bits).max().unwrap_or(0);
+  if bases.len() < PIPPENGER_THRESHOLD {
+    straus::<G>(bases, exps, bits)
+  } else {
+    pippenger::<G>(bases, exps, bits)
+  }
+}
+
+// Below this many bases the `2^w` precomputed powers per base of Straus' method are cheaper than
+// Pippenger's `2^c` buckets per window.
+const PIPPENGER_THRESHOLD: usize = 32;
+
+// The `w`-bit digit of `e` starting at bit `start`.
+fn window(e: &Integer, start: u32, w: u32) -> usize {
+  (0..w).rev().fold(0, |digit, i| (digit << 1) | e.get_bit(start + i) as usize)
+}
+
+// Multiplies `acc` by `x`, treating `None` as the identity.
+fn op_opt<G: Group>(acc: Option<G::Elem>, x: &G::Elem) -> Option<G::Elem> {
+  Some(match acc {
+    Some(acc) => G::opnew(&acc, x),
+    None => x.clone(),
+  })
+}
+
+fn square_times<G: Group>(acc: Option<G::Elem>, times: u32) -> Option<G::Elem> {
+  acc.map(|mut acc| {
+    for _ in 0..times {
+      acc = G::opnew(&acc, &acc);
+    }
+    acc
+  })
+}
+
+fn straus<G: Group>(bases: &[G::Elem], exps: &[Integer], bits: u32) -> G::Elem {
+  const W: u32 = 4;
+  // `tables[i][d - 1] = bases[i]^d` for every non-zero digit `d`.
+  let tables: Vec<Vec<G::Elem>> = bases
+    .iter()
+    .map(|x| {
+      let mut table = vec![x.clone()];
+      for d in 1..(1 << W) - 1 {
+        table.push(G::opnew(&table[d - 1], x));
+      }
+      table
+    })
+    .collect();
+
+  let mut acc = None;
+  for start in (0..bits.div_ceil(W)).rev().map(|k| k * W) {
+    acc = square_times::<G>(acc, W);
+    for (table, e) in tables.iter().zip(exps) {
+      let digit = window(e, start, W);
+      if digit != 0 {
+        acc = op_opt::<G>(acc, &table[digit - 1]);
+      }
+    }
+  }
+  acc.unwrap_or_else(G::idnew)
+}
+
+fn pippenger<G: Group>(bases: &[G::Elem], exps: &[Integer], bits: u32) -> G::Elem {
+  // About `log2(n) - 2` bits per window balances bucket filling against bucket summation.
+  let c = ((bases.len() as f64).log2() as u32).saturating_sub(2).max(2);
+  let mut acc = None;
+  for start in (0..bits.div_ceil(c)).rev().map(|k| k * c) {
+    acc = square_times::<G>(acc, c);
+    let mut buckets: Vec<Option<G::Elem>> = vec![None; (1 << c) - 1];
+    for (x, e) in bases.iter().zip(exps) {
+      let digit = window(e, start, c);
+      if digit != 0 {
+        buckets[digit - 1] = op_opt::<G>(buckets[digit - 1].take(), x);
+      }
+    }
+    // `prod_d bucket_d^d`, as the product of the running products of the top buckets.
+    let mut running = None;
+    let mut window_sum = None;
+    for bucket in buckets.iter().rev() {
+      if let Some(bucket) = bucket {
+        running = op_opt::<G>(running, bucket);
+      }
+      if let Some(running) = &running {
+        window_sum = op_opt::<G>(window_sum, running);
+      }
+    }
+    if let Some(window_sum) = &window_sum {
+      acc = op_opt::<G>(acc, window_sum);
+    }
+  }
+  acc.unwrap_or_else(G::idnew)
+}
+
+#[cfg(test)]
+mod tests;
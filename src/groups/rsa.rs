@@ -0,0 +1,298 @@
+//! RSA (2048) group using GMP integers in the `rug` crate.
+use super::{ElemFrom, ElemToBytes, Group, HashPrime, HashToGroup, UnknownOrderGroup};
+use crate::error::{Error, Result};
+use crate::hash::{hash_to_prime, hash_to_prime_bytes};
+use crate::util::{int, TypeRep};
+use rug::integer::Order;
+use lazy_static::lazy_static;
+use rug::Integer;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+/// [Follow the idea from `https://github.com/cambrian/accumulator/src/group/rsa.rs`]
+/// RSA-2048 group implementation. Modulus taken from
+/// [here](https://en.wikipedia.org/wiki/RSA_numbers#RSA-2048).
+///
+/// Elements live in the quotient group `Z_N^* / {+-1}`, which rules out the element `-1` of
+/// known order, and are represented by the smaller of `x` and `N - x`. Unlike class groups
+/// this needs a trusted setup: whoever knows the factorization of `N` knows the group order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Rsa2048 {}
+
+/// RSA-2048 modulus, taken from [Wikipedia](https://en.wikipedia.org/wiki/RSA_numbers#RSA-2048).
+const RSA2048_MODULUS_DECIMAL: &str =
+  "251959084756578934940271832400483985714292821262040320277771378360436620207075955562640185258807\
+  8440691829064124951508218929855914917618450280848912007284499268739280728777673597141834727026189\
+  6375014971824691165077613379859095700097330459748808428401797429100642458691817195118746121515172\
+  6546322822168699875491824224336372590851418654620435767984233871847744479207399342365848238242811\
+  9816381501067481045166037730605620161967625613384414360383390441495263443219011465754445417842402\
+  0924616515723350778707749817125772467962926386356373289912154831438167899885040445364023527381951\
+  378636564391212010397122822120720357";
+
+lazy_static! {
+  pub static ref RSA2048_MODULUS: Integer = Integer::from_str(RSA2048_MODULUS_DECIMAL).unwrap();
+  pub static ref HALF_MODULUS: Integer = RSA2048_MODULUS.clone() / 2;
+}
+
+/// An RSA 2048 group element, directly wrapping a GMP integer from the `rug` crate.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Rsa2048Elem(Integer);
+
+// Picks the representative of `{val, N - val}` in `[0, N / 2]` for any integer `val`.
+fn canonical(modulus: &Integer, val: Integer) -> Rsa2048Elem {
+  let (_, val) = val.div_rem_euc(modulus.clone());
+  if val > *HALF_MODULUS {
+    Rsa2048Elem(modulus - val)
+  } else {
+    Rsa2048Elem(val)
+  }
+}
+
+// Whether `x` is invertible modulo `modulus`.
+fn is_unit(modulus: &Integer, x: &Rsa2048Elem) -> bool {
+  int(x.0.gcd_ref(modulus)) == 1
+}
+
+impl TypeRep for Rsa2048 {
+  type Rep = Integer;
+  fn rep() -> &'static Self::Rep {
+    &RSA2048_MODULUS
+  }
+}
+
+impl Group for Rsa2048 {
+  type Elem = Rsa2048Elem;
+
+  fn op_(modulus: &Integer, a: &Rsa2048Elem, b: &Rsa2048Elem) -> Rsa2048Elem {
+    canonical(modulus, int(&a.0 * &b.0))
+  }
+
+  fn id_(_: &Integer) -> Rsa2048Elem {
+    Rsa2048Elem(int(1))
+  }
+
+  /// Panics if `x` shares a factor with the modulus.
+  fn inv_(modulus: &Integer, x: &Rsa2048Elem) -> Rsa2048Elem {
+    canonical(modulus, x.0.clone().invert(modulus).unwrap())
+  }
+
+  fn exp_(modulus: &Integer, x: &Rsa2048Elem, n: &Integer) -> Option<Rsa2048Elem> {
+    // A side-channel resistant impl is 40% slower; we'll consider it in the future if we need to.
+    x.0.pow_mod_ref(n, modulus).map(|val| canonical(modulus, int(val)))
+  }
+
+  /// Accepts the canonical representatives `0 < x <= N / 2` that are coprime to `N`.
+  fn validate_(modulus: &Integer, x: &Rsa2048Elem) -> Result<()> {
+    if x.0 <= 0 || x.0 > *HALF_MODULUS || !is_unit(modulus, x) {
+      return Err(Error::InvalidElement);
+    }
+    Ok(())
+  }
+}
+
+/// Fails if `t` shares a factor with the modulus, e.g. if it is `0 (mod N)`, as such values have
+/// no inverse.
+impl<T> ElemFrom<T> for Rsa2048
+where
+  Integer: From<T>,
+{
+  fn try_elem(t: T) -> Result<Rsa2048Elem> {
+    let modulus = Self::rep();
+    let x = canonical(modulus, int(t));
+    if !is_unit(modulus, &x) {
+      return Err(Error::InvalidElement);
+    }
+    Ok(x)
+  }
+}
+
+impl UnknownOrderGroup for Rsa2048 {
+  fn unknown_order_elem_(_: &Integer) -> Rsa2048Elem {
+    Self::elemnew(2)
+  }
+
+  fn size_bits_(modulus: &Integer) -> Option<u32> {
+    Some(modulus.significant_bits())
+  }
+}
+
+impl HashToGroup for Rsa2048 {
+  fn hash_to_group_(modulus: &Integer, t: &[u8]) -> Rsa2048Elem {
+    // Expand `t` to 128 bits more than the modulus so that reducing is statistically uniform.
+    let blocks = (modulus.significant_bits() as u64 + 128).div_ceil(256);
+    let mut bytes = Vec::new();
+    for block in 0..blocks {
+      let mut hasher = Sha256::new();
+      hasher.update(t);
+      hasher.update(block.to_be_bytes());
+      bytes.extend_from_slice(&hasher.finalize());
+    }
+    Self::elemnew(Integer::from_digits(&bytes[..], Order::Msf))
+  }
+}
+
+/// Elements are encoded as 256 big-endian bytes.
+impl ElemToBytes for Rsa2048 {
+  fn elem_to_bytes(x: &Rsa2048Elem) -> Vec<u8> {
+    let digits = x.0.to_digits::<u8>(Order::Msf);
+    let mut bytes = vec![0u8; 256 - digits.len()];
+    bytes.extend_from_slice(&digits);
+    bytes
+  }
+
+  fn elem_from_bytes(bytes: &[u8]) -> Option<Rsa2048Elem> {
+    if bytes.len() != 256 {
+      return None;
+    }
+    // Only the representative `elemnew` picks from `{x, N - x}` is canonical.
+    let x = Rsa2048Elem(Integer::from_digits(bytes, Order::Msf));
+    Self::validate(&x).ok().map(|_| x)
+  }
+}
+
+impl HashPrime for Rsa2048 {
+  fn pick_prime_mpz(t: &[u8]) -> Integer {
+    hash_to_prime(t)
+  }
+
+  fn pick_prime_bytes(t: &[u8]) -> [u8; 32] {
+    hash_to_prime_bytes(t)
+  }
+
+  fn pick_prime_integer(t: &[u8]) -> Integer {
+    hash_to_prime(t)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::primitives::polynomial_comm::{evaluate, PolyComm};
+
+  #[test]
+  fn test_init() {
+    let _x = &Rsa2048::rep();
+  }
+
+  #[test]
+  fn test_op() {
+    let a = Rsa2048::opnew(&Rsa2048::elemnew(2), &Rsa2048::elemnew(3));
+    assert!(a == Rsa2048::elemnew(6));
+    let b = Rsa2048::opnew(&Rsa2048::elemnew(-2), &Rsa2048::elemnew(-3));
+    assert!(b == Rsa2048::elemnew(6));
+  }
+
+  /// Tests that `-x` and `x` are treated as the same element.
+  #[test]
+  fn test_cosets() {
+    assert!(Rsa2048::elemnew(3) == Rsa2048::elemnew(RSA2048_MODULUS.clone() - 3));
+    // TODO: Add a trickier coset test involving `op`.
+  }
+
+  #[test]
+  fn test_exp() {
+    let a = Rsa2048::exp(&Rsa2048::elemnew(2), &int(3)).unwrap();
+    assert!(a == Rsa2048::elemnew(8));
+    let b = Rsa2048::exp(&Rsa2048::elemnew(2), &int(4096)).unwrap();
+    assert!(
+      b == Rsa2048::elemnew(
+        Integer::from_str(
+          "347516948011835063509026736186147670167736613636387986187783397446117477989671706360426677620\
+           804720320066290320186638217932150610511868158571651583296117329926132726067562590273846398573\
+           355191531997785006042038020289240792537963452329784467519469994729995202851277215294386454597\
+           215781915401755405133097389723652788563211688623201939420774863047011745733979212850435573010\
+           252827198413528147794405916692255653102089135177887096203901996365389989975680240069634968836\
+           160224765234872100366388507585224056308876130758064337947852953771156755956468824992247494057\
+           1457237038298492875257462065516606150126302256771725610647"
+        )
+        .unwrap()
+      )
+    );
+  }
+
+  #[test]
+  fn test_inv() {
+    let x = Rsa2048::elemnew(2);
+    let inv = Rsa2048::invnew(&x);
+    assert!(Rsa2048::opnew(&x, &inv) == Rsa2048::idnew());
+  }
+
+  #[test]
+  fn test_exp_negative() {
+    let x = Rsa2048::unknown_order_elemnew();
+    let n = int(12345);
+    let pos = Rsa2048::exp(&x, &n).unwrap();
+    let neg = Rsa2048::exp(&x, &int(-&n)).unwrap();
+    assert_eq!(Rsa2048::opnew(&pos, &neg), Rsa2048::idnew());
+  }
+
+  #[test]
+  fn test_poly_comm() {
+    let d_max = 5;
+    let pp = PolyComm::<Rsa2048, Integer>::setup(2048, d_max);
+    let f: Vec<Integer> = (0..=d_max as i32).map(|i| int(i * 1000 - 2500)).collect();
+    let comm = PolyComm::commit(&pp, &f);
+    assert!(PolyComm::verify_open(&pp, &comm, &f));
+
+    let z = int(42);
+    let y = evaluate(&f, &z, &pp.p);
+    let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+    assert!(PolyComm::eval_verify(&pp, &comm, &z, &y, &proof));
+    assert!(!PolyComm::eval_verify(&pp, &comm, &z, &int(&y + 1), &proof));
+  }
+
+  #[test]
+  fn test_hash_to_group() {
+    crate::groups::tests::check_hash_to_group::<Rsa2048>();
+  }
+
+  #[test]
+  fn test_multi_exp_general() {
+    crate::groups::tests::check_multi_exp_general::<Rsa2048>();
+  }
+
+  #[test]
+  fn test_elem_bytes() {
+    crate::groups::tests::check_elem_bytes::<Rsa2048>();
+    let x = Rsa2048::elemnew(5);
+    assert_eq!(Rsa2048::elem_to_bytes(&x).len(), 256);
+    // `N - 5` is the same element as `5`, but not its canonical representative.
+    let other: Integer = RSA2048_MODULUS.clone() - 5;
+    assert_eq!(Rsa2048::elem_from_bytes(&other.to_digits::<u8>(Order::Msf)), None);
+    assert_eq!(Rsa2048::elem_from_bytes(&[0u8; 256]), None);
+  }
+
+  #[test]
+  fn test_validate() {
+    for x in &[Rsa2048::idnew(), Rsa2048::elemnew(5), Rsa2048::hash_to_groupnew(b"validate")] {
+      assert_eq!(Rsa2048::validate(x), Ok(()));
+    }
+    let modulus = Rsa2048::rep();
+    let non_canonical = Rsa2048Elem(int(modulus - 5));
+    for x in &[Rsa2048Elem(int(0)), Rsa2048Elem(int(-5)), non_canonical, Rsa2048Elem(modulus.clone())] {
+      assert_eq!(Rsa2048::validate(x), Err(Error::InvalidElement));
+    }
+  }
+
+  #[test]
+  fn test_try_elem() {
+    let modulus = Rsa2048::rep();
+    assert_eq!(Rsa2048::try_elem(int(modulus - 5)), Ok(Rsa2048::elemnew(5)));
+    for x in &[int(0), modulus.clone(), int(-modulus), int(modulus * 3)] {
+      assert_eq!(Rsa2048::try_elem(x.clone()), Err(Error::InvalidElement));
+    }
+  }
+
+  #[should_panic]
+  #[test]
+  fn test_elemnew_zero() {
+    Rsa2048::elemnew(0);
+  }
+
+  #[test]
+  fn test_hash_to_prime() {
+    let p = Rsa2048::pick_prime_integer(b"boom i got ur boyfriend");
+    assert_eq!(Integer::from_digits(&Rsa2048::pick_prime_bytes(b"boom i got ur boyfriend"), Order::Msf), p);
+    assert_eq!(Rsa2048::pick_prime_mpz(b"boom i got ur boyfriend"), p);
+  }
+}
@@ -0,0 +1,483 @@
+//! Tests shared by every class group backend, plus cross-checks between the backends that are
+//! enabled together.
+use super::{ElemToBytes, Group, HashToGroup, UnknownOrderGroup};
+use crate::error::Error;
+use crate::util::int;
+use rand::Rng;
+use rug::integer::Order;
+use rug::ops::Pow;
+use rug::Integer;
+use std::str::FromStr;
+
+// Chia's sample implementation gives `(4, 1, C4) * (16, 41, C16) = (64, 9, C64)`, see
+// `classgroupsti.rs`.
+const C4: &str = "\
+  19135043146754702466933535947700509509683047735103167439198117911126500023332446530136407244\
+  268818886844950990589400824048541137030123517295048625578863052039394052606960429510076477727\
+  812619793559333896857655440664448190570209733309248852860771133554929587999582285331513410741\
+  679548532925359795754799072731031327175516868367484717873943724678975890638662600637655379895\
+  797691446827331865910685793896910463236233398285859677535633644394859647446063344540995395360\
+  815557919878168193309083573295900545539758915028677094752412489256178770608972743880695597825\
+  16229851064188563419476497892884550353389340326220747256139";
+const C16: &str = "\
+  47837607866886756167333839869251273774207619337757918597995294777816250058331116325341018110\
+  672047217112377476473502060121352842575308793237621563947157630098485131517401073775191194319\
+  531549483898334742144138601661120476425524333273122132151927833887323969998955713328783526854\
+  198871332313399489386997681827578317938792170918711794684859311697439726596656501594138449739\
+  494228617068329664776714484742276158090583495714649193839084110987149118615158361352488488402\
+  038894799695420483272708933239751363849397287571692736881031223140446926522431859701738994562\
+  9057462766047140854869124473221137588347335081555186814061";
+const C64: &str = "\
+  11959401966721689041833459967312818443551904834439479649498823694454062514582779081335254527\
+  668011804278094369118375515030338210643827198309405390986789407524621282879350268443797798579\
+  882887370974583685536034650415280119106381083318280533037981958471830992499738928332195881713\
+  549717833078349872346749420456894579484698042729677948671214827924359931649164125398534612434\
+  873557154267082416194178621185569039522645873928662298459771027746787279653789590338122122100\
+  50972369992385512081817723330993784096234932189292318422025780578511173163060796492543474864\
+  07264365691511785213717281118305284397086833770388796703509";
+
+fn ints(a: i32, b: i32, c: &str) -> Triple {
+    (int(a), int(b), Integer::from_str(c).unwrap())
+}
+
+type Triple = (Integer, Integer, Integer);
+
+// The forms `(a, b, a * b)` of the ground truth above.
+fn ground_truth() -> (Triple, Triple, Triple) {
+    (ints(4, 1, C4), ints(16, 41, C16), ints(64, 9, C64))
+}
+
+pub(crate) fn check_op_ground_truth<G: Group>(elem: impl Fn(Triple) -> G::Elem) {
+    let (a, b, c) = ground_truth();
+    assert_eq!(G::opnew(&elem(a), &elem(b)), elem(c));
+}
+
+pub(crate) fn check_id<G: UnknownOrderGroup>() {
+    let g = G::unknown_order_elemnew();
+    let id = G::idnew();
+    assert_eq!(g, G::opnew(&g, &id));
+    assert_eq!(g, G::opnew(&id, &g));
+    assert_eq!(id, G::opnew(&id, &id));
+}
+
+pub(crate) fn check_inv<G: UnknownOrderGroup>() {
+    let id = G::idnew();
+    let g_anchor = G::unknown_order_elemnew();
+    let mut g = g_anchor.clone();
+    for _ in 0..100 {
+        g = G::opnew(&g, &g_anchor);
+        let g_inv = G::invnew(&g);
+        assert_eq!(id, G::opnew(&g, &g_inv));
+        assert_eq!(g, G::invnew(&g_inv));
+    }
+}
+
+pub(crate) fn check_exp<G: UnknownOrderGroup>() {
+    let g_anchor = G::unknown_order_elemnew();
+    let mut g = G::idnew();
+    for i in 0..100 {
+        assert_eq!(g, G::exp(&g_anchor, &int(i)).unwrap());
+        assert_eq!(G::invnew(&g), G::exp(&g_anchor, &int(-i)).unwrap());
+        g = G::opnew(&g, &g_anchor);
+    }
+
+    // `g^(m + n) = g^m * g^n` and `(g^m)^n = g^(m n)` for exponents wider than a limb.
+    let m = int(3).pow(80);
+    let n = int(7).pow(50);
+    let g_m = G::exp(&g_anchor, &m).unwrap();
+    let g_n = G::exp(&g_anchor, &n).unwrap();
+    assert_eq!(G::exp(&g_anchor, &int(&m + &n)).unwrap(), G::opnew(&g_m, &g_n));
+    assert_eq!(G::exp(&g_anchor, &int(&m * &n)).unwrap(), G::exp(&g_m, &n).unwrap());
+}
+
+/// The binary square-and-multiply `exp` that the windowed default replaced, kept as a reference.
+fn binary_exp<G: Group>(a: &G::Elem, n: &Integer) -> G::Elem {
+    let (mut val, mut a, mut n) = if *n < 0 {
+        (G::idnew(), G::invnew(a), int(-n))
+    } else {
+        (G::idnew(), a.clone(), n.clone())
+    };
+    while n > 0 {
+        if n.is_odd() {
+            val = G::opnew(&val, &a);
+        }
+        a = G::opnew(&a, &a);
+        n >>= 1;
+    }
+    val
+}
+
+/// Checks the default `Group::exp_` against `binary_exp` for random exponents of every window width.
+pub(crate) fn check_exp_matches_binary<G: HashToGroup + UnknownOrderGroup>() {
+    let mut rng = rand::thread_rng();
+    let a = G::hash_to_groupnew(b"exp");
+    for &bytes in &[1, 4, 5, 16, 32, 33, 64] {
+        for _ in 0..4 {
+            let n = Integer::from_digits(&(0..bytes).map(|_| rng.gen()).collect::<Vec<u8>>(), Order::Msf);
+            assert_eq!(G::exp(&a, &n).unwrap(), binary_exp::<G>(&a, &n));
+            assert_eq!(G::exp(&a, &int(-&n)).unwrap(), binary_exp::<G>(&a, &int(-&n)));
+        }
+    }
+    // All ones is the worst case for binary and the best for NAF.
+    let n = (int(1) << 300) - 1;
+    assert_eq!(G::exp(&a, &n).unwrap(), binary_exp::<G>(&a, &n));
+}
+
+#[test]
+fn test_wnaf() {
+    for w in 2..=5 {
+        for n in -1000..1000 {
+            let digits = super::wnaf(&int(n), w);
+            assert!(digits.last().map_or(n == 0, |d| *d != 0));
+            let sum = digits.iter().rev().fold(int(0), |sum, d| (sum << 1) + *d);
+            assert_eq!(sum, n);
+            for (i, d) in digits.iter().enumerate() {
+                if *d != 0 {
+                    assert!(d % 2 != 0 && d.abs() < 1 << (w - 1));
+                    assert!(digits[i + 1..].iter().take(w as usize - 1).all(|d| *d == 0));
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn check_hash_to_group<G: HashToGroup + UnknownOrderGroup>() {
+    let x = G::hash_to_groupnew(b"supersonic");
+    assert_eq!(x, G::hash_to_groupnew(b"supersonic"));
+    assert_ne!(x, G::hash_to_groupnew(b"supersonid"));
+    assert_ne!(x, G::idnew());
+    assert_ne!(x, G::unknown_order_elemnew());
+
+    let elems: Vec<_> = (0..20u8).map(|i| G::hash_to_groupnew(&[i])).collect();
+    for (i, x) in elems.iter().enumerate() {
+        assert!(elems[..i].iter().all(|y| y != x));
+        assert_eq!(G::opnew(x, &G::invnew(x)), G::idnew());
+    }
+}
+
+pub(crate) fn check_elem_bytes<G: ElemToBytes + HashToGroup + UnknownOrderGroup>() {
+    let mut elems = vec![G::idnew(), G::unknown_order_elemnew()];
+    elems.extend((0..10u8).map(|i| G::hash_to_groupnew(&[i])));
+    elems.push(G::invnew(&elems[2]));
+    for x in &elems {
+        let bytes = G::elem_to_bytes(x);
+        assert_eq!(G::elem_from_bytes(&bytes).as_ref(), Some(x));
+        assert_eq!(G::elem_from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(G::elem_from_bytes(&[&bytes[..], &[0]].concat()), None);
+    }
+    assert_eq!(G::elem_from_bytes(&[]), None);
+}
+
+/// Checks that `validate` accepts the elements the group produces, and that class group backends
+/// reject the forms built by `raw` (without reduction) that are not reduced or have another
+/// discriminant.
+pub(crate) fn check_validate<G: HashToGroup + UnknownOrderGroup>(raw: impl Fn(Triple) -> G::Elem) {
+    let x = G::hash_to_groupnew(b"validate");
+    let mut elems = vec![G::idnew(), G::unknown_order_elemnew(), x.clone(), G::invnew(&x)];
+    elems.push(G::exp(&x, &int(3).pow(100)).unwrap());
+    for x in &elems {
+        assert_eq!(G::validate(x), Ok(()));
+    }
+
+    // `(4, 1, C4)` is reduced.
+    let ((a, b, c), _, _) = ground_truth();
+    assert_eq!(G::validate(&raw((a.clone(), b.clone(), c.clone()))), Ok(()));
+    let unreduced = raw((a.clone(), int(&b + int(&a * 2)), int(&a + &b) + &c));
+    let negated = raw((int(-&a), b.clone(), int(-&c)));
+    let other_disc = raw((a.clone(), b.clone(), int(&c + 1)));
+    for x in &[unreduced, negated, other_disc] {
+        assert_eq!(G::validate(x), Err(Error::InvalidElement));
+    }
+}
+
+/// Checks that class group backends encode only `(a, b)` and decode nothing but reduced forms of
+/// the right discriminant.
+pub(crate) fn check_form_bytes<G>(abc: impl Fn(&G::Elem) -> Triple)
+where
+    G: ElemToBytes + HashToGroup,
+{
+    use super::classgroup::form_to_bytes;
+    let x = G::hash_to_groupnew(b"form");
+    let (a, b, _) = abc(&x);
+    assert_eq!(G::elem_to_bytes(&x), form_to_bytes(&a, &b));
+
+    // `(a, b + 2a)` is equivalent to `(a, b)` but not reduced.
+    let unreduced = form_to_bytes(&a, &int(&b + int(&a * 2)));
+    assert_eq!(G::elem_from_bytes(&unreduced), None);
+    // `b` has the wrong parity, so there is no integer `c`.
+    let off_disc = form_to_bytes(&a, &int(&b + 1));
+    assert_eq!(G::elem_from_bytes(&off_disc), None);
+    let negative = form_to_bytes(&int(-&a), &b);
+    assert_eq!(G::elem_from_bytes(&negative), None);
+    // Other values of `a` rarely make `b^2 - disc` divisible by `4a`.
+    let wrong_a = form_to_bytes(&int(&a + 2), &b);
+    assert_eq!(G::elem_from_bytes(&wrong_a), None);
+}
+
+pub(crate) fn check_multi_exp_general<G: HashToGroup>() {
+    use super::multi_exp_general;
+    let naive = |bases: &[G::Elem], exps: &[Integer]| {
+        bases
+            .iter()
+            .zip(exps)
+            .fold(G::idnew(), |acc, (x, e)| G::opnew(&acc, &G::exp(x, e).unwrap()))
+    };
+    assert_eq!(multi_exp_general::<G>(&[], &[]), G::idnew());
+
+    // Sizes on both sides of the switch to Pippenger's method, with exponents that are
+    // negative, zero, small and wider than a window.
+    for &n in &[1, 2, 5, 31, 32, 70] {
+        let bases: Vec<G::Elem> = (0..n as u8).map(|i| G::hash_to_groupnew(&[i])).collect();
+        let exps: Vec<Integer> = (0..n as u32)
+            .map(|i| match i % 4 {
+                0 => int(3).pow(40 + i) - i,
+                1 => -int(5).pow(i + 1),
+                2 => int(0),
+                _ => int(i),
+            })
+            .collect();
+        assert_eq!(multi_exp_general::<G>(&bases, &exps), naive(&bases, &exps));
+    }
+}
+
+/// Runs `f` on a pool of four threads, so that the parallel code paths are taken on machines
+/// with fewer cores as well.
+#[cfg(feature = "parallel")]
+pub(crate) fn in_pool<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap().install(f)
+}
+
+#[cfg(feature = "parallel")]
+mod parallel {
+    use super::*;
+    type ClassGroup = crate::groups::classgroup::ClassGroup;
+    use crate::groups::{multi_exp, multi_exp_general, multi_exp_unsigned, try_multi_exp};
+
+    #[test]
+    fn test_multi_exp_general_parallel() {
+        let bases: Vec<_> = (0..40u8).map(|i| ClassGroup::hash_to_groupnew(&[i])).collect();
+        let exps: Vec<Integer> = (0..40u32).map(|i| int(7).pow(20 + i) + i).collect();
+        let sequential = multi_exp_unsigned::<ClassGroup>(&bases, &exps);
+        assert_eq!(in_pool(|| multi_exp_general::<ClassGroup>(&bases, &exps)), sequential);
+    }
+
+    #[test]
+    fn test_multi_exp_parallel() {
+        let x: Vec<Integer> = [3, 5, 7, 11, 13, 17].iter().map(|&p| int(p)).collect();
+        // `alphas[i] = g^(x_i)`, so the product of `alphas[i]^(x* / x_i)` is `g^(n x*)`.
+        let g = ClassGroup::unknown_order_elemnew();
+        let alphas: Vec<_> = x.iter().map(|x_i| ClassGroup::exp(&g, x_i).unwrap()).collect();
+        let x_star: Integer = x.iter().product();
+        let expected = ClassGroup::exp(&g, &(x_star * x.len() as u32)).unwrap();
+        assert_eq!(in_pool(|| multi_exp::<ClassGroup>(&alphas, &x)), expected);
+        assert_eq!(multi_exp::<ClassGroup>(&alphas, &x), expected);
+        assert_eq!(in_pool(|| try_multi_exp::<ClassGroup>(&alphas, &x)), Ok(expected));
+    }
+}
+
+mod default_validate {
+    use super::*;
+    use crate::util::TypeRep;
+    use lazy_static::lazy_static;
+
+    // The multiplicative group modulo 23, with elements in `[0, 23)`, as an external group would
+    // implement it without `validate_`.
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    enum Mod23 {}
+
+    impl TypeRep for Mod23 {
+        type Rep = Integer;
+
+        fn rep() -> &'static Integer {
+            lazy_static! {
+                static ref MODULUS: Integer = int(23);
+            }
+            &MODULUS
+        }
+    }
+
+    impl Group for Mod23 {
+        type Elem = Integer;
+
+        fn id_(_: &Integer) -> Integer {
+            int(1)
+        }
+
+        fn op_(p: &Integer, a: &Integer, b: &Integer) -> Integer {
+            int(a * b) % p
+        }
+
+        fn inv_(p: &Integer, a: &Integer) -> Integer {
+            a.clone().invert(p).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_default_validate() {
+        assert_eq!(Mod23::validate(&int(5)), Ok(()));
+        assert_eq!(Mod23::validate(&int(28)), Err(Error::InvalidElement));
+        assert_eq!(Mod23::exp(&int(5), &int(2)), Some(int(2)));
+    }
+}
+
+#[cfg(feature = "class_group_sti")]
+mod classygroup {
+    // The ground truth, identity and inverse checks are among the tests in `classgroupsti.rs`.
+    use super::*;
+    use crate::groups::classgroupsti::{ClassElem, ClassyGroup, Mpz};
+
+    #[test]
+    fn test_exp() {
+        check_exp::<ClassyGroup>();
+    }
+
+    #[test]
+    fn test_hash_to_group() {
+        check_hash_to_group::<ClassyGroup>();
+    }
+
+    #[test]
+    fn test_multi_exp_general() {
+        check_multi_exp_general::<ClassyGroup>();
+    }
+
+    #[test]
+    fn test_elem_bytes() {
+        check_elem_bytes::<ClassyGroup>();
+        check_form_bytes::<ClassyGroup>(|x| {
+            let int = |m: &Mpz| Integer::from_str(&m.to_string()).unwrap();
+            (int(&x.a), int(&x.b), int(&x.c))
+        });
+    }
+
+    #[test]
+    fn test_validate() {
+        check_validate::<ClassyGroup>(|(a, b, c)| {
+            let mpz = |x: Integer| Mpz::from_str(&x.to_string()).unwrap();
+            ClassElem { a: mpz(a), b: mpz(b), c: mpz(c) }
+        });
+    }
+}
+
+#[cfg(feature = "class_group_zen")]
+mod zen {
+    use super::*;
+    use crate::groups::ElemFrom;
+    use crate::groups::classgroupzen::{ZenClassElem, ZenClassGroup};
+
+    #[test]
+    fn test_op_ground_truth() {
+        check_op_ground_truth::<ZenClassGroup>(ZenClassGroup::elemnew);
+    }
+
+    #[test]
+    fn test_id() {
+        check_id::<ZenClassGroup>();
+    }
+
+    #[test]
+    fn test_inv() {
+        check_inv::<ZenClassGroup>();
+    }
+
+    #[test]
+    fn test_exp() {
+        check_exp::<ZenClassGroup>();
+    }
+
+    #[test]
+    fn test_hash_to_group() {
+        check_hash_to_group::<ZenClassGroup>();
+    }
+
+    #[test]
+    fn test_multi_exp_general() {
+        check_multi_exp_general::<ZenClassGroup>();
+    }
+
+    #[test]
+    fn test_elem_bytes() {
+        check_elem_bytes::<ZenClassGroup>();
+        check_form_bytes::<ZenClassGroup>(|x| (x.a.clone(), x.b.clone(), x.c.clone()));
+    }
+
+    #[test]
+    fn test_validate() {
+        check_validate::<ZenClassGroup>(|(a, b, c)| ZenClassElem { a, b, c });
+    }
+
+    #[should_panic]
+    #[test]
+    fn test_bad_elem() {
+        let _ = ZenClassGroup::elemnew((int(1), int(2), int(3)));
+    }
+
+    #[test]
+    fn test_try_elem() {
+        let bad = ZenClassGroup::try_elem((int(1), int(2), int(3)));
+        assert_eq!(bad, Err(crate::error::Error::InvalidElement));
+        let x = ZenClassGroup::unknown_order_elemnew();
+        assert_eq!(ZenClassGroup::try_elem((x.a.clone(), x.b.clone(), x.c.clone())), Ok(x));
+    }
+}
+
+#[cfg(all(feature = "class_group_sti", feature = "class_group_zen"))]
+mod cross {
+    use super::*;
+    use crate::groups::classgroupsti::{ClassElem, ClassyGroup};
+    use crate::groups::classgroupzen::{ZenClassElem, ZenClassGroup};
+    use crate::util::TypeRep;
+
+    fn to_zen(x: &ClassElem) -> ZenClassElem {
+        let int = |m: &dyn ToString| Integer::from_str(&m.to_string()).unwrap();
+        ZenClassElem {
+            a: int(&x.a),
+            b: int(&x.b),
+            c: int(&x.c),
+        }
+    }
+
+    #[test]
+    fn test_same_group() {
+        assert_eq!(ClassyGroup::rep().to_string(), ZenClassGroup::rep().to_string());
+        assert_eq!(to_zen(&ClassyGroup::idnew()), ZenClassGroup::idnew());
+        assert_eq!(
+            to_zen(&ClassyGroup::unknown_order_elemnew()),
+            ZenClassGroup::unknown_order_elemnew()
+        );
+    }
+
+    #[test]
+    fn test_hash_to_group_agrees() {
+        for i in 0..10u8 {
+            assert_eq!(to_zen(&ClassyGroup::hash_to_groupnew(&[i])), ZenClassGroup::hash_to_groupnew(&[i]));
+        }
+    }
+
+    #[test]
+    fn test_elem_bytes_agree() {
+        for i in 0..10u8 {
+            let x = ClassyGroup::hash_to_groupnew(&[i]);
+            let bytes = ClassyGroup::elem_to_bytes(&x);
+            assert_eq!(bytes, ZenClassGroup::elem_to_bytes(&to_zen(&x)));
+            assert_eq!(ZenClassGroup::elem_from_bytes(&bytes), Some(to_zen(&x)));
+        }
+    }
+
+    #[test]
+    fn test_ops_agree() {
+        let g_sti = ClassyGroup::unknown_order_elemnew();
+        let g_zen = ZenClassGroup::unknown_order_elemnew();
+        let (mut x_sti, mut x_zen) = (g_sti.clone(), g_zen.clone());
+        for i in 0..50 {
+            let e = int(3).pow(i) + i;
+            let y_sti = ClassyGroup::exp(&x_sti, &e).unwrap();
+            let y_zen = ZenClassGroup::exp(&x_zen, &e).unwrap();
+            assert_eq!(to_zen(&y_sti), y_zen);
+            assert_eq!(to_zen(&ClassyGroup::invnew(&y_sti)), ZenClassGroup::invnew(&y_zen));
+            x_sti = ClassyGroup::opnew(&y_sti, &g_sti);
+            x_zen = ZenClassGroup::opnew(&y_zen, &g_zen);
+            assert_eq!(to_zen(&x_sti), x_zen);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Hashing into primes, for groups that do not bring their own `HashPrime` implementation.
+use rug::integer::{IsPrime, Order};
+use rug::Integer;
+use sha2::{Digest, Sha256};
+
+// Number of Miller-Rabin rounds on top of the Baillie-PSW test GMP runs first.
+const MILLER_RABIN_REPS: u32 = 25;
+
+/// Hashes `t` to a 256-bit prime: the first probable prime among `SHA256(t || counter)` for
+/// `counter = 0, 1, ...`, with the top bit set so that every output has exactly 256 bits.
+pub fn hash_to_prime(t: &[u8]) -> Integer {
+  let mut counter = 0u64;
+  loop {
+    let mut hasher = Sha256::new();
+    hasher.update(t);
+    hasher.update(counter.to_be_bytes());
+    let mut candidate = Integer::from_digits(&hasher.finalize()[..], Order::Msf);
+    candidate.set_bit(255, true);
+    if candidate.is_probably_prime(MILLER_RABIN_REPS) != IsPrime::No {
+      return candidate;
+    }
+    counter += 1;
+  }
+}
+
+/// `hash_to_prime(t)` as 32 big-endian bytes, the form `HashPrime::pick_prime_bytes` returns.
+pub fn hash_to_prime_bytes(t: &[u8]) -> [u8; 32] {
+  let mut bytes = [0u8; 32];
+  bytes.copy_from_slice(&hash_to_prime(t).to_digits::<u8>(Order::Msf));
+  bytes
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_hash_to_prime() {
+    let p_1 = hash_to_prime(b"boom i got ur boyfriend");
+    let p_2 = hash_to_prime(b"boom i got ur boyfriene");
+    assert_ne!(p_1, p_2);
+    assert_eq!(p_1, hash_to_prime(b"boom i got ur boyfriend"));
+    assert_eq!(Integer::from_digits(&hash_to_prime_bytes(b"boom i got ur boyfriend"), Order::Msf), p_1);
+    for p in &[p_1, p_2] {
+      assert_eq!(p.significant_bits(), 256);
+      assert_ne!(p.is_probably_prime(50), IsPrime::No);
+    }
+  }
+}
@@ -1,10 +1,15 @@
 
+pub mod error;
 pub mod util;
+pub mod hash;
 pub mod groups;
 pub mod primitives;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod transcript;
 
 
-#[cfg(feature = "class_group")]
+#[cfg(feature = "class_group_sti")]
 extern crate classygroup;
 
 #[cfg(test)]
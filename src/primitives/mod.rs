@@ -1 +1,3 @@
-pub mod polynomial_comm;
\ No newline at end of file
+pub mod poe;
+pub mod poke;
+pub mod polynomial_comm;
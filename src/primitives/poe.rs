@@ -0,0 +1,180 @@
+use crate::groups::{ElemToBytes, UnknownOrderGroup, HashPrime};
+use crate::transcript::Transcript;
+use rug::Integer;
+use rug::ops::Pow;
+#[cfg(feature = "serde")]
+use crate::serialization::{deserialize_elem, serialize_elem, versioned};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [Follow the idea in `https://github.com/cambrian/accumulator/src/proof/poe.rs`]
+/// Wesolowski's non-interactive proof of exponentiation (PoE), as described in
+/// BBF (https://eprint.iacr.org/2018/1188.pdf), section 3.1.
+/// Convinces a verifier that `base^(q^m) = result` while the verifier only exponentiates by
+/// the challenge prime `l` and by `q^m mod l`, which it computes without ever forming `q^m`.
+#[allow(non_snake_case)]
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
+pub struct PoE<G: UnknownOrderGroup> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
+    pub Q: G::Elem,
+}
+
+#[cfg(feature = "serde")]
+versioned!(PoE<G> where G: ElemToBytes + UnknownOrderGroup);
+
+impl<G: UnknownOrderGroup + HashPrime + ElemToBytes> PoE<G> {
+    /// Computes a proof that `base^(q^m) = result`. This is the only place `q^m` is formed.
+    ///
+    /// Panics if `q` is negative and `base` has no inverse.
+    pub fn prove(base: &G::Elem, q: &Integer, m: u32, result: &G::Elem) -> PoE<G> {
+        Self::prove_in(&mut Self::transcript(base, q, m, result), base, q, m)
+    }
+
+    /// Same as `prove`, but draws the challenge from `transcript`, which has to bind `base`, `q`,
+    /// `m` and `result` already. This spares the verifier of a larger protocol from computing
+    /// `result` when it is implied by the protocol's earlier messages.
+    pub(crate) fn prove_in(transcript: &mut Transcript<G>, base: &G::Elem, q: &Integer, m: u32) -> PoE<G> {
+        let l = transcript.challenge_prime(b"l");
+        let (quo, _) = q.clone().pow(m).div_rem_euc(l);
+        PoE { Q: G::exp(base, &quo).expect("the base is not invertible") }
+    }
+
+    /// Verifies a proof that `base^(q^m) = result`, by checking `Q^l * base^(q^m mod l) = result`,
+    /// which fails unless `base`, `result` and `Q` are valid elements.
+    pub fn verify(base: &G::Elem, q: &Integer, m: u32, result: &G::Elem, proof: &PoE<G>) -> bool {
+        if [base, result, &proof.Q].iter().any(|x| G::validate(x).is_err()) {
+            return false;
+        }
+        let (l, r) = Self::exponents(base, q, m, result);
+        match (G::exp(&proof.Q, &l), G::exp(base, &r)) {
+            (Some(q_l), Some(base_r)) => G::opnew(&q_l, &base_r) == *result,
+            _ => false,
+        }
+    }
+
+    /// The exponents `(l, q^m mod l)` of the check `Q^l * base^(q^m mod l) = result`, for
+    /// verifiers that batch it with other checks. The elements are not validated.
+    pub(crate) fn exponents(base: &G::Elem, q: &Integer, m: u32, result: &G::Elem) -> (Integer, Integer) {
+        Self::exponents_in(&mut Self::transcript(base, q, m, result), q, m)
+    }
+
+    /// Same as `exponents`, for a proof from `prove_in` with the same `transcript`.
+    pub(crate) fn exponents_in(transcript: &mut Transcript<G>, q: &Integer, m: u32) -> (Integer, Integer) {
+        let l = transcript.challenge_prime(b"l");
+        let r = pow_mod(q, m, &l);
+        (l, r)
+    }
+
+    // The transcript of a standalone proof, from which the challenge prime
+    // `l = H_prime(base, q, m, result)` is drawn.
+    fn transcript(base: &G::Elem, q: &Integer, m: u32, result: &G::Elem) -> Transcript<G> {
+        let mut transcript = Transcript::<G>::new(b"poe");
+        transcript.append_elem(b"base", base);
+        transcript.append_integer(b"q", q);
+        transcript.append_u64(b"m", u64::from(m));
+        transcript.append_elem(b"result", result);
+        transcript
+    }
+}
+
+// `q^m mod l` in `[0, l)`, the remainder of the Euclidean division of `q^m` by `l`.
+fn pow_mod(q: &Integer, m: u32, l: &Integer) -> Integer {
+    q.clone().pow_mod(&Integer::from(m), l).expect("a positive exponent always has a power")
+}
+
+#[cfg(all(test, feature = "class_group_sti"))]
+mod tests {
+    use super::*;
+    use crate::groups::Group;
+    use crate::groups::classgroupsti::{ClassElem, ClassyGroup, Mpz};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_poe_small_exp() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let q = Integer::from(20);
+        let result = ClassyGroup::exp(&base, &q).unwrap();
+        let proof = PoE::<ClassyGroup>::prove(&base, &q, 1, &result);
+        assert!(PoE::verify(&base, &q, 1, &result, &proof));
+        // The quotient is zero, so `Q` is the identity.
+        assert_eq!(proof.Q, ClassyGroup::idnew());
+    }
+
+    #[test]
+    fn test_poe_large_exp() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let q = (Integer::from(1) << 2048) + 12345;
+        let result = ClassyGroup::exp(&base, &q).unwrap();
+        let proof = PoE::<ClassyGroup>::prove(&base, &q, 1, &result);
+        assert!(PoE::verify(&base, &q, 1, &result, &proof));
+
+        let neg_q = Integer::from(-&q);
+        let neg_result = ClassyGroup::invnew(&result);
+        let neg_proof = PoE::<ClassyGroup>::prove(&base, &neg_q, 1, &neg_result);
+        assert!(PoE::verify(&base, &neg_q, 1, &neg_result, &neg_proof));
+    }
+
+    #[test]
+    fn test_poe_power() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        for (q, m) in &[(Integer::from(3), 1000), (Integer::from(-7), 301), (Integer::from(-7), 300)] {
+            let result = ClassyGroup::exp(&base, &q.clone().pow(*m)).unwrap();
+            let proof = PoE::<ClassyGroup>::prove(&base, q, *m, &result);
+            assert!(PoE::verify(&base, q, *m, &result, &proof));
+            assert!(!PoE::verify(&base, q, *m + 1, &result, &proof));
+        }
+    }
+
+    #[test]
+    fn test_poe_tampered() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let (q, m) = (Integer::from(5), 400);
+        let result = ClassyGroup::exp(&base, &q.clone().pow(m)).unwrap();
+        let proof = PoE::<ClassyGroup>::prove(&base, &q, m, &result);
+
+        let tampered = PoE::<ClassyGroup> { Q: ClassyGroup::opnew(&proof.Q, &base) };
+        assert!(!PoE::verify(&base, &q, m, &result, &tampered));
+
+        let wrong_result = ClassyGroup::opnew(&result, &base);
+        assert!(!PoE::verify(&base, &q, m, &wrong_result, &proof));
+        assert!(!PoE::verify(&base, &Integer::from(&q + 1), m, &result, &proof));
+
+        let other_base = ClassyGroup::opnew(&base, &base);
+        assert!(!PoE::verify(&other_base, &q, m, &result, &proof));
+    }
+
+    #[test]
+    fn test_poe_rejects_invalid_elements() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let (q, m) = (Integer::from(5), 400);
+        let result = ClassyGroup::exp(&base, &q.clone().pow(m)).unwrap();
+        let proof = PoE::<ClassyGroup>::prove(&base, &q, m, &result);
+
+        // `(a, b + 2a, a + b + c)` is equivalent to `Q`, so only validation catches it.
+        let int = |m: &Mpz| Integer::from_str(&m.to_string()).unwrap();
+        let mpz = |x: Integer| Mpz::from_str(&x.to_string()).unwrap();
+        let (a, b, c) = (int(&proof.Q.a), int(&proof.Q.b), int(&proof.Q.c));
+        let unreduced = ClassElem { a: mpz(a.clone()), b: mpz(Integer::from(&a * 2) + &b), c: mpz(a + b + c) };
+        assert!(!PoE::verify(&base, &q, m, &result, &PoE::<ClassyGroup> { Q: unreduced.clone() }));
+        assert!(!PoE::verify(&unreduced, &q, m, &result, &proof));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_poe_serde() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let q = Integer::from(5);
+        let result = ClassyGroup::exp(&base, &q.clone().pow(40u32)).unwrap();
+        let proof = PoE::<ClassyGroup>::prove(&base, &q, 40, &result);
+
+        let bytes = bincode::serialize(&proof).unwrap();
+        assert_eq!(bincode::deserialize::<PoE<ClassyGroup>>(&bytes).unwrap(), proof);
+
+        // The version comes first.
+        let mut bad_version = bytes;
+        bad_version[0] ^= 1;
+        assert!(bincode::deserialize::<PoE<ClassyGroup>>(&bad_version).is_err());
+    }
+}
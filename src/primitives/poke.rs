@@ -0,0 +1,158 @@
+use crate::groups::{ElemToBytes, UnknownOrderGroup, HashPrime};
+use crate::transcript::Transcript;
+use rug::Integer;
+#[cfg(feature = "serde")]
+use crate::serialization::{deserialize_elem, serialize_elem, versioned};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [Follow the idea in `https://github.com/cambrian/accumulator/src/proof/poke2.rs`]
+/// Non-interactive proof of knowledge of exponent (PoKE2), as described in
+/// BBF (https://eprint.iacr.org/2018/1188.pdf), section 3.3.
+/// Proves knowledge of an integer `x` with `base^x = result` without revealing `x`; the proof
+/// is three elements regardless of the size of `x`. The challenges `l` (a prime) and `alpha`
+/// of the interactive protocol are derived from a `Transcript`.
+#[allow(non_snake_case)]
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
+pub struct PoKE2<G: UnknownOrderGroup> {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
+    pub z: G::Elem,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
+    pub Q: G::Elem,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub r: Integer,
+}
+
+#[cfg(feature = "serde")]
+versioned!(PoKE2<G> where G: ElemToBytes + UnknownOrderGroup);
+
+#[allow(non_snake_case)]
+impl<G: UnknownOrderGroup + HashPrime + ElemToBytes> PoKE2<G> {
+    /// Computes a proof that the prover knows `exp` such that `base^exp = result`.
+    ///
+    /// Panics if `exp` is negative and `base` has no inverse.
+    pub fn prove(base: &G::Elem, exp: &Integer, result: &G::Elem) -> PoKE2<G> {
+        let g = G::unknown_order_elemnew();
+        let z = G::exp(&g, exp).expect("the unknown-order element is invertible");
+        let (l, alpha) = Self::challenges(base, result, &z);
+        let (q, r) = exp.clone().div_rem_euc(l);
+        let g_alpha = G::exp(&g, &alpha).expect("non-negative exponents always succeed");
+        let base_g_alpha = G::opnew(base, &g_alpha);
+        let Q = G::exp(&base_g_alpha, &q).expect("the base is not invertible");
+        PoKE2 { z, Q, r }
+    }
+
+    /// Verifies a proof of knowledge of an exponent `x` with `base^x = result`, by checking
+    /// `Q^l * (base * g^alpha)^r = result * z^alpha`. Any invalid element makes it fail, as does
+    /// a remainder `r` outside `[0, l)`.
+    pub fn verify(base: &G::Elem, result: &G::Elem, proof: &PoKE2<G>) -> bool {
+        let PoKE2 { z, Q, r } = proof;
+        if [base, result, z, Q].iter().any(|x| G::validate(x).is_err()) {
+            return false;
+        }
+        let (l, alpha) = Self::challenges(base, result, z);
+        if *r < 0 || *r >= l {
+            return false;
+        }
+        Self::check(base, result, proof, &l, &alpha).unwrap_or(false)
+    }
+
+    // The check `Q^l * (base * g^alpha)^r = result * z^alpha`, or `None` if an exponentiation
+    // fails.
+    fn check(base: &G::Elem, result: &G::Elem, proof: &PoKE2<G>, l: &Integer, alpha: &Integer) -> Option<bool> {
+        let PoKE2 { z, Q, r } = proof;
+        let g = G::unknown_order_elemnew();
+        let base_g_alpha = G::opnew(base, &G::exp(&g, alpha)?);
+        let lhs = G::opnew(&G::exp(Q, l)?, &G::exp(&base_g_alpha, r)?);
+        let rhs = G::opnew(result, &G::exp(z, alpha)?);
+        Some(lhs == rhs)
+    }
+
+    // The challenge prime `l = H_prime(base, result, z)` and the 128-bit `alpha = H(base, result,
+    // z, l)`.
+    fn challenges(base: &G::Elem, result: &G::Elem, z: &G::Elem) -> (Integer, Integer) {
+        let mut transcript = Transcript::<G>::new(b"poke2");
+        transcript.append_elem(b"base", base);
+        transcript.append_elem(b"result", result);
+        transcript.append_elem(b"z", z);
+        let l = transcript.challenge_prime(b"l");
+        let alpha = transcript.challenge_bits(b"alpha", 128);
+        (l, alpha)
+    }
+}
+
+#[cfg(all(test, feature = "class_group_sti"))]
+mod tests {
+    use super::*;
+    use crate::groups::Group;
+    use crate::groups::classgroupsti::ClassyGroup;
+
+    #[test]
+    fn test_poke2() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let base = ClassyGroup::opnew(&base, &base);
+        for exp in &[Integer::from(20), (Integer::from(1) << 1024) + 7, Integer::from(-12345)] {
+            let result = ClassyGroup::exp(&base, exp).unwrap();
+            let proof = PoKE2::<ClassyGroup>::prove(&base, exp, &result);
+            assert!(PoKE2::verify(&base, &result, &proof));
+        }
+    }
+
+    #[test]
+    fn test_poke2_rejects_wrong_statement() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let exp = (Integer::from(1) << 512) + 3;
+        let result = ClassyGroup::exp(&base, &exp).unwrap();
+        let proof = PoKE2::<ClassyGroup>::prove(&base, &exp, &result);
+
+        let wrong_result = ClassyGroup::opnew(&result, &base);
+        assert!(!PoKE2::verify(&base, &wrong_result, &proof));
+
+        let other_base = ClassyGroup::opnew(&base, &base);
+        assert!(!PoKE2::verify(&other_base, &result, &proof));
+    }
+
+    #[test]
+    fn test_poke2_tampered() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let exp = (Integer::from(1) << 512) + 3;
+        let result = ClassyGroup::exp(&base, &exp).unwrap();
+        let proof = PoKE2::<ClassyGroup>::prove(&base, &exp, &result);
+
+        let mut bad = proof.clone();
+        bad.z = ClassyGroup::opnew(&bad.z, &base);
+        assert!(!PoKE2::verify(&base, &result, &bad));
+
+        let mut bad = proof.clone();
+        bad.Q = ClassyGroup::opnew(&bad.Q, &base);
+        assert!(!PoKE2::verify(&base, &result, &bad));
+
+        let mut bad = proof.clone();
+        bad.r += 1;
+        assert!(!PoKE2::verify(&base, &result, &bad));
+
+        // `r` must be reduced modulo `l`.
+        let mut bad = proof;
+        bad.r = Integer::from(-1);
+        assert!(!PoKE2::verify(&base, &result, &bad));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_poke2_serde() {
+        let base = ClassyGroup::unknown_order_elemnew();
+        let exp = (Integer::from(1) << 512) + 3;
+        let result = ClassyGroup::exp(&base, &exp).unwrap();
+        let proof = PoKE2::<ClassyGroup>::prove(&base, &exp, &result);
+
+        let bytes = bincode::serialize(&proof).unwrap();
+        assert_eq!(bincode::deserialize::<PoKE2<ClassyGroup>>(&bytes).unwrap(), proof);
+
+        // The version comes first.
+        let mut bad_version = bytes;
+        bad_version[0] ^= 1;
+        assert!(bincode::deserialize::<PoKE2<ClassyGroup>>(&bad_version).is_err());
+    }
+}
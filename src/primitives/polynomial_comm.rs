@@ -1,7 +1,19 @@
-use crate::groups::{UnknownOrderGroup, HashPrime, ElemFrom};
+use crate::error::{Error, Result};
+use crate::groups::{multi_exp_general, ElemToBytes, Group, UnknownOrderGroup, HashPrime, HashToGroup};
+use crate::groups::fixed_base::FixedBaseTable;
+use crate::primitives::poe::PoE;
+use crate::transcript::Transcript;
 use rand::Rng;
 use rug::Integer;
+use rug::integer::{IsPrime, Order};
+use rug::ops::Pow;
+#[cfg(feature = "serde")]
+use crate::serialization::{deserialize_elem, serialize_elem, versioned};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg, Sub};
 
 /// [Follow the idea in `https://github.com/ZenGo-X/class/src/primitives`]
 /// AND [Follow the idea in `https://github.com/dignifiedquire/rust-accumulator/src/accumulator`]
@@ -17,49 +29,957 @@ use std::marker::PhantomData;
 /// Eval_verify: NI verifier for eval_proof.
 
 #[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
 pub struct PP<G: UnknownOrderGroup, T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
     phantom: PhantomData<*const T>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
     pub disc: G::Elem,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
     pub g: G::Elem,
-    pub q: G::Elem,
-    pub p: G::Elem,
+    /// The security parameter the parameters were derived for.
+    pub lambda: usize,
+    /// The base at which polynomials are encoded, `p^(2 * log(d_max + 1) + 1)`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub q: Integer,
+    /// The prime modulus the committed polynomials are evaluated over.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub p: Integer,
+    /// The maximum degree of a committed polynomial.
+    pub d_max: usize,
+    /// The bound `(p - 1) / 2` on the absolute value of committed coefficients.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub b: Integer,
 }
 
 
 #[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
 pub struct PolyComm<G: UnknownOrderGroup, T> {
+    #[cfg_attr(feature = "serde", serde(skip))]
     phantom: PhantomData<T>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
     pub c: G::Elem,
 }
 
-impl<G:  ElemFrom<(Integer, Integer, Integer)> + HashPrime + UnknownOrderGroup, T> PolyComm<G, T> {    
+/// The prover's messages for one halving round of the evaluation protocol: commitments to
+/// the low and high halves `f_L`, `f_R` of the current polynomial, their evaluations, and a
+/// proof that `C_R^(q^m) = C / C_L`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "G: ElemToBytes"))]
+pub struct EvalRound<G: UnknownOrderGroup> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub y_l: Integer,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub y_r: Integer,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
+    pub c_l: G::Elem,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
+    pub c_r: G::Elem,
+    pub poe: PoE<G>,
+}
+
+/// Non-interactive proof that a committed polynomial of degree at most `d` evaluates to `y`
+/// at `z` modulo `p`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
+pub struct EvalProof<G: UnknownOrderGroup> {
+    /// The degree bound the proof was produced for.
+    pub d: usize,
+    pub rounds: Vec<EvalRound<G>>,
+    /// The constant polynomial left after the last round, sent in the clear.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub f0: Integer,
+}
+
+/// Non-interactive proof that a committed polynomial evaluates to `ys[i]` at `zs[i]` modulo `p`
+/// for every `i`.
+///
+/// The prover commits to the quotient `h` of `f` by the vanishing polynomial `Z` of the points,
+/// so that `f - I = Z h (mod p)` for the interpolation `I` of the claimed evaluations. The
+/// identity is checked at a random point `r` through the evaluations `y_f = f(r)` and
+/// `y_h = h(r)`, which are in turn proven at once as in `PolyComm::eval_prove_batch`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
+pub struct MultiEvalProof<G: UnknownOrderGroup> {
+    /// The commitment to the quotient `h`.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_elem::<G, _>", deserialize_with = "deserialize_elem::<G, _>"))]
+    pub c_h: G::Elem,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub y_f: Integer,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub y_h: Integer,
+    /// The batched evaluation proof for `f` and `h` at `r`.
+    pub eval: EvalProof<G>,
+}
+
+/// Non-interactive proof that a committed polynomial has degree at most `d`, for any `d` up to
+/// `PP::d_max`.
+///
+/// This is an evaluation proof at `0` for degree bound `d`: its halving rounds only end in a
+/// constant with coefficients bounded by `p^rounds * b` if the committed integer polynomial has
+/// at most `d + 1` coefficients (subsection 4.3 of the paper), whatever the evaluation point is.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self", bound = "G: ElemToBytes"))]
+pub struct DegreeProof<G: UnknownOrderGroup> {
+    /// The constant coefficient of the committed polynomial modulo `p`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::integer"))]
+    pub y: Integer,
+    pub eval: EvalProof<G>,
+}
+
+/// An evaluation proof together with its statement `(comm, z, y, proof)`, as verified in batches by
+/// `PolyComm::batch_eval_verify`.
+pub type EvalClaim<G, T> = (PolyComm<G, T>, Integer, Integer, EvalProof<G>);
+
+#[cfg(feature = "serde")]
+versioned!(PP<G, T> where G: ElemToBytes + UnknownOrderGroup);
+#[cfg(feature = "serde")]
+versioned!(PolyComm<G, T> where G: ElemToBytes + UnknownOrderGroup);
+#[cfg(feature = "serde")]
+versioned!(EvalProof<G> where G: ElemToBytes + UnknownOrderGroup);
+#[cfg(feature = "serde")]
+versioned!(MultiEvalProof<G> where G: ElemToBytes + UnknownOrderGroup);
+#[cfg(feature = "serde")]
+versioned!(DegreeProof<G> where G: ElemToBytes + UnknownOrderGroup);
+
+// Group elements and exponents whose multi-exponentiation a verifier checks to be the identity.
+type Equation<G> = (Vec<<G as Group>::Elem>, Vec<Integer>);
+
+impl<G: HashPrime + HashToGroup + ElemToBytes + UnknownOrderGroup, T> PP<G, T> {
+    /// Checks that these parameters are exactly the ones `PolyComm::setup_from_seed` derives
+    /// from `seed` for their `lambda` and `d_max`.
+    pub fn verify_derivation(&self, seed: &[u8]) -> bool {
+        let pp = PolyComm::<G, T>::setup_from_seed(seed, self.lambda, self.d_max);
+        pp.disc == self.disc && pp.g == self.g && pp.p == self.p && pp.q == self.q && pp.b == self.b
+    }
+
+    /// Checks that these parameters are consistent: `lambda` is the size of `G`, `p` is prime,
+    /// and `q`, `b` and `d_max` are related as `PolyComm::setup` derives them. Deserialized
+    /// parameters can be anything, so the `try_*` functions of `PolyComm` check them first.
+    pub fn validate(&self) -> Result<()> {
+        if matches!(G::size_bits(), Some(bits) if bits as usize != self.lambda) {
+            return Err(Error::UnsupportedLambda);
+        }
+        if self.p <= 2 || self.p.is_probably_prime(30) == IsPrime::No {
+            return Err(Error::InvalidParameters);
+        }
+        if encoding_base(&self.p, self.d_max)? != self.q || Integer::from(&self.p - 1) / 2 != self.b {
+            return Err(Error::InvalidParameters);
+        }
+        Ok(())
+    }
+
+    /// Precomputes powers of `g` for `PolyComm::commit_with_table`, storing at most `max_elems`
+    /// group elements. The table can be serialized and kept next to the parameters.
+    pub fn fixed_base_table(&self, max_elems: usize) -> FixedBaseTable<G> {
+        // Encodings of polynomials with coefficients below `q / 2` are below `q^(d_max + 1)`.
+        let max_bits = (self.d_max as u32 + 1) * self.q.significant_bits();
+        FixedBaseTable::new(&self.g, max_bits, max_elems)
+    }
+}
+
+impl<G: HashPrime + HashToGroup + ElemToBytes + UnknownOrderGroup, T> PolyComm<G, T> {    
     // `d_max` is the max degree of the polynomial
-    pub fn setup( _lambda: usize, d_max: usize) -> PP<G, T>
+    pub fn setup(lambda: usize, d_max: usize) -> PP<G, T>
     {
+        Self::try_setup(lambda, d_max).expect("invalid setup parameters")
+    }
+
+    /// Same as `setup`, but fails with `Error::DegreeTooLarge` instead of panicking if no
+    /// parameters support polynomials of degree `d_max`, and with `Error::UnsupportedLambda` if
+    /// `lambda` is not the size of `G`.
+    pub fn try_setup(lambda: usize, d_max: usize) -> Result<PP<G, T>> {
+        let seed = rand::thread_rng().gen::<[u8; 32]>();
+        Self::try_setup_from_seed(&seed, lambda, d_max)
+    }
+
+    /// Derives the public parameters from a public `seed`, so that anyone can recompute them
+    /// with `PP::verify_derivation` and no party knows a trapdoor for them.
+    ///
+    /// `lambda` is the bit size of the group, `G::size_bits()`, which the type `G` fixes: a
+    /// class group over a discriminant of another size is a different `G`.
+    pub fn setup_from_seed(seed: &[u8], lambda: usize, d_max: usize) -> PP<G, T> {
+        Self::try_setup_from_seed(seed, lambda, d_max).expect("invalid setup parameters")
+    }
+
+    /// Same as `setup_from_seed`, but fails with `Error::DegreeTooLarge` instead of panicking if
+    /// no parameters support polynomials of degree `d_max`, and with `Error::UnsupportedLambda`
+    /// if `lambda` is not the size of `G`.
+    pub fn try_setup_from_seed(seed: &[u8], lambda: usize, d_max: usize) -> Result<PP<G, T>> {
+        if matches!(G::size_bits(), Some(bits) if bits as usize != lambda) {
+            return Err(Error::UnsupportedLambda);
+        }
         let disc = G::unknown_order_elemnew();
 
-        let g = G::unknown_order_elemnew();
+        let mut transcript = b"supersonic.setup".to_vec();
+        transcript.extend_from_slice(&(seed.len() as u64).to_be_bytes());
+        transcript.extend_from_slice(seed);
+        transcript.extend_from_slice(&(lambda as u64).to_be_bytes());
+        transcript.extend_from_slice(&(d_max as u64).to_be_bytes());
+        let label = |label: &[u8]| [&transcript[..], label].concat();
+
+        let g = G::hash_to_groupnew(&label(b"g"));
+        let p = G::pick_prime_integer(&label(b"p"));
+
+        let q = encoding_base(&p, d_max)?;
+        let b = Integer::from(&p - 1) / 2;
+        Ok(PP::<G, T>  { phantom: PhantomData, disc, g, lambda, p, q, d_max, b})
+    }
+
+    /// Commits to the integer polynomial `f` by raising `pp.g` to its encoding `f(q)`.
+    pub fn commit(pp: &PP<G, T>, f: &[Integer]) -> PolyComm<G, T> {
+        // `pp.g` comes from `hash_to_group` or passed `validate` on deserialization, so it has an
+        // inverse even if the encoding is negative.
+        let c = G::exp(&pp.g, &encode(f, &pp.q)).expect("the generator is invertible");
+        PolyComm { phantom: PhantomData, c }
+    }
+
+    /// Same as `commit`, but fails instead of committing to a polynomial that no opening or
+    /// evaluation proof would be accepted for: `f` must have degree at most `pp.d_max` and
+    /// coefficients bounded by `pp.b`.
+    pub fn try_commit(pp: &PP<G, T>, f: &[Integer]) -> Result<PolyComm<G, T>> {
+        pp.validate()?;
+        Self::check_poly(pp, f)?;
+        let c = G::exp(&pp.g, &encode(f, &pp.q)).ok_or(Error::InvalidElement)?;
+        Ok(PolyComm { phantom: PhantomData, c })
+    }
+
+    /// Same as `commit`, but raises `pp.g` with a table from `PP::fixed_base_table`.
+    pub fn commit_with_table(pp: &PP<G, T>, table: &FixedBaseTable<G>, f: &[Integer]) -> PolyComm<G, T> {
+        assert!(*table.base() == pp.g, "the table is not for the generator of these parameters");
+        PolyComm { phantom: PhantomData, c: table.exp(&encode(f, &pp.q)) }
+    }
 
-        let mut random_bytes = rand::thread_rng().gen::<[u8; 32]>();
-        let a = G::pick_prime_integer(&random_bytes);
-        random_bytes = rand::thread_rng().gen::<[u8; 32]>();
-        let b = G::pick_prime_integer(&random_bytes);
-        random_bytes = rand::thread_rng().gen::<[u8; 32]>();
-        let c = G::pick_prime_integer(&random_bytes);
+    /// Opens a commitment. Commitments carry no blinding randomness, so the opening is the
+    /// committed polynomial itself.
+    pub fn open(f: &[Integer]) -> Vec<Integer> {
+        f.to_vec()
+    }
 
-        let p = G::elemnew((a, b, c));
+    /// Checks that `f` is an opening of `comm`: its degree is at most `pp.d_max`, every
+    /// coefficient is bounded by `pp.b` in absolute value, and it commits to `comm`.
+    ///
+    /// The bound check is what makes the opening unique; without it, `f + q - X` would open
+    /// the same commitment as `f`.
+    pub fn verify_open(pp: &PP<G, T>, comm: &PolyComm<G, T>, f: &[Integer]) -> bool {
+        Self::try_verify_open(pp, comm, f).is_ok()
+    }
 
-        let bound =  2 * (((d_max + 1) as f64).log2() as usize) + 1;
-        let q = G::exp(&p, &Integer::from(bound)).unwrap();
-        PP::<G, T>  { phantom: PhantomData, disc, g, p, q}
+    /// Same as `verify_open`, but reports why the opening was rejected.
+    pub fn try_verify_open(pp: &PP<G, T>, comm: &PolyComm<G, T>, f: &[Integer]) -> Result<()> {
+        G::validate(&comm.c)?;
+        if Self::try_commit(pp, f)?.c != comm.c {
+            return Err(Error::BadProof);
+        }
+        Ok(())
+    }
 
+    // Checks the degree and coefficient bounds of `pp` on `f`.
+    fn check_poly(pp: &PP<G, T>, f: &[Integer]) -> Result<()> {
+        if !matches!(pp.d_max.checked_add(1), Some(n) if f.len() <= n) {
+            return Err(Error::DegreeTooLarge);
+        }
+        if f.iter().any(|coeff| coeff.cmp_abs(&pp.b) == Ordering::Greater) {
+            return Err(Error::CoefficientOutOfBound);
+        }
+        Ok(())
+    }
+
+    /// Proves that the polynomial `f`, committed to by `Self::commit(pp, f)`, evaluates to
+    /// `evaluate(f, z, p)` at `z`. `p` has to be the modulus of `pp`.
+    ///
+    /// Each round splits `f = f_L + X^m f_R`, sends commitments and evaluations for both halves,
+    /// and folds them into `f_L + alpha f_R` for a Fiat-Shamir challenge `alpha`, halving the
+    /// degree until a constant is left (subsection 4.3 of the paper).
+    pub fn eval_prove(pp: &PP<G, T>, f: &[Integer], z: &Integer, p: &Integer) -> EvalProof<G> {
+        let mut f = if f.is_empty() { vec![Integer::new()] } else { f.to_vec() };
+        let d = f.len() - 1;
+        let c = Self::commit(pp, &f).c;
+        let mut transcript = Self::eval_transcript(pp, &c, z, &evaluate(&f, z, p), d);
+
+        let mut rounds = Vec::new();
+        while f.len() > 1 {
+            if f.len() % 2 == 1 {
+                // Multiply by `X` to make the number of coefficients even.
+                f.insert(0, Integer::new());
+            }
+            let m = f.len() / 2;
+            let f_r = f.split_off(m);
+            let f_l = f;
+            let c_l = Self::commit(pp, &f_l).c;
+            let c_r = Self::commit(pp, &f_r).c;
+            let (y_l, y_r) = (evaluate(&f_l, z, p), evaluate(&f_r, z, p));
+            Self::absorb_halves(&mut transcript, &c_l, &c_r, &y_l, &y_r);
+            // C_R^(q^m) = C / C_L
+            let poe = PoE::prove_in(&mut transcript, &c_r, &pp.q, m as u32);
+            let round = EvalRound { y_l, y_r, c_l, c_r, poe };
+            let alpha = Self::eval_challenge(pp, &mut transcript, &round.poe);
+
+            f = f_l
+                .iter()
+                .zip(f_r.iter())
+                .map(|(l, r)| Integer::from(&alpha * r) + l)
+                .collect();
+            rounds.push(round);
+        }
+        EvalProof { d, rounds, f0: f.remove(0) }
+    }
+
+    /// Verifies that `comm` commits to a polynomial of degree at most `proof.d` with integer
+    /// coefficients bounded by `pp.b`, which evaluates to `y` at `z` modulo `pp.p`.
+    pub fn eval_verify(
+        pp: &PP<G, T>,
+        comm: &PolyComm<G, T>,
+        z: &Integer,
+        y: &Integer,
+        proof: &EvalProof<G>,
+    ) -> bool {
+        Self::try_eval_verify(pp, comm, z, y, proof).is_ok()
+    }
+
+    /// Same as `eval_verify`, but reports why the proof was rejected.
+    pub fn try_eval_verify(
+        pp: &PP<G, T>,
+        comm: &PolyComm<G, T>,
+        z: &Integer,
+        y: &Integer,
+        proof: &EvalProof<G>,
+    ) -> Result<()> {
+        pp.validate()?;
+        check_equations::<G>(Self::eval_equations(pp, &comm.c, z, y, &pp.b, proof)?)
+    }
+
+    /// Verifies many evaluation proofs for commitments under the same parameters, accepting if
+    /// `eval_verify` accepts every `(comm, z, y, proof)` of `batch`, and rejecting otherwise
+    /// except with probability about `2^-128`.
+    ///
+    /// The group equations of all proofs are raised to independent random 128-bit exponents and
+    /// multiplied, so a single multi-exponentiation checks them together, including the powers
+    /// `q` and `q^m mod l` of the commitments. Like the PoE itself, this relies on nobody knowing
+    /// elements of small order. Outside the multi-exponentiation, every round of every proof
+    /// still costs one exponentiation by its challenge `alpha < p` to fold the commitment, and
+    /// one hash to a prime.
+    pub fn batch_eval_verify(pp: &PP<G, T>, batch: &[EvalClaim<G, T>]) -> bool {
+        Self::try_batch_eval_verify(pp, batch).is_ok()
+    }
+
+    /// Same as `batch_eval_verify`, but reports why the batch was rejected. A failed group equation
+    /// shows up as `Error::BadProof` without saying which proof it belongs to.
+    pub fn try_batch_eval_verify(pp: &PP<G, T>, batch: &[EvalClaim<G, T>]) -> Result<()> {
+        pp.validate()?;
+        let mut rng = rand::thread_rng();
+        // Every proof ends with an equation in `g`, so its exponents are collected separately.
+        let mut g_exp = Integer::new();
+        let mut bases = Vec::new();
+        let mut exps = Vec::new();
+        for (comm, z, y, proof) in batch {
+            for (eq_bases, eq_exps) in Self::eval_equations(pp, &comm.c, z, y, &pp.b, proof)? {
+                let rho = Integer::from_digits(&rng.gen::<[u8; 16]>(), Order::Msf);
+                for (base, exp) in eq_bases.into_iter().zip(eq_exps) {
+                    if base == pp.g {
+                        g_exp += exp * &rho;
+                    } else {
+                        bases.push(base);
+                        exps.push(exp * &rho);
+                    }
+                }
+            }
+        }
+        bases.push(pp.g.clone());
+        exps.push(g_exp);
+        if multi_exp_general::<G>(&bases, &exps) != G::idnew() {
+            return Err(Error::BadProof);
+        }
+        Ok(())
+    }
+
+    // Replays the verifier of `proof` for the commitment `c` to a polynomial with coefficients
+    // bounded by `b`, and performs every check but the group equations, which it returns as
+    // `(bases, exps)` such that the proof is valid iff each `prod_i bases[i]^exps[i]` is the
+    // identity.
+    fn eval_equations(
+        pp: &PP<G, T>,
+        c: &G::Elem,
+        z: &Integer,
+        y: &Integer,
+        b: &Integer,
+        proof: &EvalProof<G>,
+    ) -> Result<Vec<Equation<G>>> {
+        if proof.d > pp.d_max {
+            return Err(Error::DegreeTooLarge);
+        }
+        G::validate(c)?;
+        for round in &proof.rounds {
+            G::validate(&round.c_l)?;
+            G::validate(&round.c_r)?;
+            G::validate(&round.poe.Q)?;
+        }
+        let p = &pp.p;
+        let mut n = proof.d + 1;
+        // The current commitment is `c^k`. Raising it to `k = q` is left to the equations, where it
+        // costs nothing extra in a multi-exponentiation.
+        let mut c = c.clone();
+        let mut k = Integer::from(1);
+        let mut y = reduce(y.clone(), p);
+        let mut b = b.clone();
+        let mut transcript = Self::eval_transcript(pp, &c, z, &y, proof.d);
+
+        let mut equations = Vec::new();
+        let mut rounds = proof.rounds.iter();
+        while n > 1 {
+            if n % 2 == 1 {
+                // Multiplying by `X` raises the commitment to the power `q`.
+                n += 1;
+                k = pp.q.clone();
+                y = reduce(y * z, p);
+            }
+            let round = match rounds.next() {
+                Some(round) => round,
+                None => return Err(Error::BadProof),
+            };
+            let m = n / 2;
+
+            // y = y_L + z^m y_R (mod p)
+            let z_m = z.clone().pow_mod(&Integer::from(m), p).unwrap();
+            if reduce(z_m * &round.y_r + &round.y_l - &y, p) != 0 {
+                return Err(Error::BadProof);
+            }
+            // C^k = C_L * C_R^(q^m), checked succinctly through the PoE: Q^l * C_R^r * C_L = C^k.
+            Self::absorb_halves(&mut transcript, &round.c_l, &round.c_r, &round.y_l, &round.y_r);
+            let (l, r) = PoE::<G>::exponents_in(&mut transcript, &pp.q, m as u32);
+            equations.push((
+                vec![round.poe.Q.clone(), round.c_r.clone(), round.c_l.clone(), c],
+                vec![l, r, Integer::from(1), -k],
+            ));
+
+            let alpha = Self::eval_challenge(pp, &mut transcript, &round.poe);
+            c = G::opnew(&round.c_l, &G::exp(&round.c_r, &alpha).ok_or(Error::InvalidElement)?);
+            k = Integer::from(1);
+            y = reduce(Integer::from(&alpha * &round.y_r) + &round.y_l, p);
+            // |f_L + alpha f_R| <= b + alpha b <= p b
+            b *= p;
+            n = m;
+        }
+        if rounds.next().is_some() {
+            return Err(Error::BadProof);
+        }
+
+        if proof.f0.cmp_abs(&b) == Ordering::Greater {
+            return Err(Error::CoefficientOutOfBound);
+        }
+        if reduce(proof.f0.clone(), p) != y {
+            return Err(Error::BadProof);
+        }
+        // g^f0 = C^k
+        equations.push((vec![pp.g.clone(), c], vec![proof.f0.clone(), -k]));
+        Ok(equations)
+    }
+
+    /// Proves that the polynomial `f`, committed to by `Self::commit(pp, f)`, evaluates to
+    /// `evaluate(f, z, p)` at every point `z` of `zs`. The points have to be distinct modulo `p`,
+    /// which has to be the modulus of `pp`.
+    pub fn eval_prove_multi(pp: &PP<G, T>, f: &[Integer], zs: &[Integer], p: &Integer) -> MultiEvalProof<G> {
+        let ys: Vec<Integer> = zs.iter().map(|z| evaluate(f, z, p)).collect();
+        // `I` has degree below `Z`, so the quotient of `f` by `Z` is the quotient of `f - I`.
+        let (h, _) = div_rem_monic(f, &vanishing_poly(zs, p), p);
+        let h: Vec<Integer> = h.into_iter().map(|coeff| balance(coeff, p)).collect();
+        let c_f = Self::commit(pp, f).c;
+        let c_h = Self::commit(pp, &h).c;
+
+        let mut transcript = Self::multi_transcript(pp, &c_f, zs, &ys);
+        let r = Self::multi_point(pp, &mut transcript, &c_h);
+        let (y_f, y_h) = (evaluate(f, &r, p), evaluate(&h, &r, p));
+        let eval = Self::prove_batch(pp, &[f, &h], &[c_f, c_h.clone()], &r, p);
+        MultiEvalProof { c_h, y_f, y_h, eval }
+    }
+
+    /// Verifies that `comm` commits to a polynomial with integer coefficients bounded by `pp.b`
+    /// which evaluates to `ys[i]` at `zs[i]` modulo `pp.p` for every `i`. Costs about as much as
+    /// `eval_verify`, whatever the number of points.
+    pub fn eval_verify_multi(
+        pp: &PP<G, T>,
+        comm: &PolyComm<G, T>,
+        zs: &[Integer],
+        ys: &[Integer],
+        proof: &MultiEvalProof<G>,
+    ) -> bool {
+        Self::try_eval_verify_multi(pp, comm, zs, ys, proof).is_ok()
+    }
+
+    /// Same as `eval_verify_multi`, but reports why the proof was rejected.
+    pub fn try_eval_verify_multi(
+        pp: &PP<G, T>,
+        comm: &PolyComm<G, T>,
+        zs: &[Integer],
+        ys: &[Integer],
+        proof: &MultiEvalProof<G>,
+    ) -> Result<()> {
+        pp.validate()?;
+        let p = &pp.p;
+        if zs.len() != ys.len() {
+            return Err(Error::BadProof);
+        }
+        G::validate(&comm.c)?;
+        G::validate(&proof.c_h)?;
+        let ys: Vec<Integer> = ys.iter().map(|y| reduce(y.clone(), p)).collect();
+
+        let mut transcript = Self::multi_transcript(pp, &comm.c, zs, &ys);
+        let r = Self::multi_point(pp, &mut transcript, &proof.c_h);
+
+        // f(r) - I(r) = Z(r) h(r) (mod p)
+        let i_r = interpolate_at(zs, &ys, &r, p).ok_or(Error::BadProof)?;
+        let z_r = evaluate(&vanishing_poly(zs, p), &r, p);
+        if reduce(Integer::from(&z_r * &proof.y_h) + &i_r - &proof.y_f, p) != 0 {
+            return Err(Error::BadProof);
+        }
+
+        let cs = [comm.c.clone(), proof.c_h.clone()];
+        let ys = [proof.y_f.clone(), proof.y_h.clone()];
+        check_equations::<G>(Self::batch_equations(pp, &cs, &r, &ys, &proof.eval)?)
+    }
+
+    /// Proves that the polynomials `fs`, each committed to by `Self::commit(pp, f)`, evaluate to
+    /// `evaluate(f, z, p)` at the same point `z`. `p` has to be the modulus of `pp`.
+    ///
+    /// The polynomials are folded into `sum_i gamma_i f_i` with `gamma_0 = 1` and 128-bit
+    /// Fiat-Shamir challenges `gamma_i`, whose commitment is `prod_i C_i^gamma_i`, and a single
+    /// evaluation proof is produced for the folded polynomial.
+    pub fn eval_prove_batch(pp: &PP<G, T>, fs: &[Vec<Integer>], z: &Integer, p: &Integer) -> EvalProof<G> {
+        let fs: Vec<&[Integer]> = fs.iter().map(|f| &f[..]).collect();
+        let cs: Vec<G::Elem> = fs.iter().map(|f| Self::commit(pp, f).c).collect();
+        Self::prove_batch(pp, &fs, &cs, z, p)
+    }
+
+    /// Verifies that every `comms[i]` commits to a polynomial with integer coefficients bounded by
+    /// `pp.b` which evaluates to `ys[i]` at `z` modulo `pp.p`. Costs one multi-exponentiation with
+    /// 128-bit exponents on top of `eval_verify`.
+    pub fn eval_verify_batch(
+        pp: &PP<G, T>,
+        comms: &[PolyComm<G, T>],
+        z: &Integer,
+        ys: &[Integer],
+        proof: &EvalProof<G>,
+    ) -> bool {
+        Self::try_eval_verify_batch(pp, comms, z, ys, proof).is_ok()
+    }
+
+    /// Same as `eval_verify_batch`, but reports why the proof was rejected.
+    pub fn try_eval_verify_batch(
+        pp: &PP<G, T>,
+        comms: &[PolyComm<G, T>],
+        z: &Integer,
+        ys: &[Integer],
+        proof: &EvalProof<G>,
+    ) -> Result<()> {
+        pp.validate()?;
+        let cs: Vec<G::Elem> = comms.iter().map(|comm| comm.c.clone()).collect();
+        check_equations::<G>(Self::batch_equations(pp, &cs, z, ys, proof)?)
+    }
+
+    /// Proves that the polynomial `f`, committed to by `Self::commit(pp, f)`, has degree at most
+    /// `d`, so that a single `pp` serves polynomials of every degree up to `pp.d_max`.
+    ///
+    /// Panics if `f` has more than `d + 1` coefficients.
+    pub fn prove_degree_bound(pp: &PP<G, T>, f: &[Integer], d: usize) -> DegreeProof<G> {
+        assert!(f.len() <= d + 1, "the polynomial has more than d + 1 coefficients");
+        let mut f = f.to_vec();
+        f.resize(d + 1, Integer::new());
+        let z = Integer::new();
+        let y = evaluate(&f, &z, &pp.p);
+        let eval = Self::eval_prove(pp, &f, &z, &pp.p);
+        DegreeProof { y, eval }
+    }
+
+    /// Verifies that `comm` commits to a polynomial of degree at most `d` with integer
+    /// coefficients bounded by `pp.b`.
+    pub fn verify_degree_bound(pp: &PP<G, T>, comm: &PolyComm<G, T>, d: usize, proof: &DegreeProof<G>) -> bool {
+        Self::try_verify_degree_bound(pp, comm, d, proof).is_ok()
+    }
+
+    /// Same as `verify_degree_bound`, but reports why the proof was rejected.
+    pub fn try_verify_degree_bound(
+        pp: &PP<G, T>,
+        comm: &PolyComm<G, T>,
+        d: usize,
+        proof: &DegreeProof<G>,
+    ) -> Result<()> {
+        pp.validate()?;
+        if d > pp.d_max {
+            return Err(Error::DegreeTooLarge);
+        }
+        // A proof for a larger bound says nothing about `d`.
+        if proof.eval.d != d {
+            return Err(Error::BadProof);
+        }
+        Self::try_eval_verify(pp, comm, &Integer::new(), &proof.y, &proof.eval)
+    }
+
+    // Folds the polynomials `fs` with commitments `cs` and proves the evaluation of the result.
+    fn prove_batch(pp: &PP<G, T>, fs: &[&[Integer]], cs: &[G::Elem], z: &Integer, p: &Integer) -> EvalProof<G> {
+        let ys: Vec<Integer> = fs.iter().map(|f| evaluate(f, z, p)).collect();
+        let gammas = Self::batch_challenges(pp, cs, z, &ys);
+        let mut folded = vec![Integer::new(); fs.iter().map(|f| f.len()).max().unwrap_or(0)];
+        for (f, gamma) in fs.iter().zip(&gammas) {
+            for (folded_i, f_i) in folded.iter_mut().zip(f.iter()) {
+                *folded_i += Integer::from(gamma * f_i);
+            }
+        }
+        Self::eval_prove(pp, &folded, z, p)
+    }
+
+    // The equations `eval_equations` gives for the folded commitment and evaluation.
+    fn batch_equations(
+        pp: &PP<G, T>,
+        cs: &[G::Elem],
+        z: &Integer,
+        ys: &[Integer],
+        proof: &EvalProof<G>,
+    ) -> Result<Vec<Equation<G>>> {
+        if cs.len() != ys.len() {
+            return Err(Error::BadProof);
+        }
+        for c in cs {
+            G::validate(c)?;
+        }
+        let ys: Vec<Integer> = ys.iter().map(|y| reduce(y.clone(), &pp.p)).collect();
+        let gammas = Self::batch_challenges(pp, cs, z, &ys);
+        let c = multi_exp_general::<G>(cs, &gammas);
+        let y = gammas.iter().zip(&ys).fold(Integer::new(), |y, (gamma, y_i)| y + Integer::from(gamma * y_i));
+        // |sum_i gamma_i f_i| <= sum_i gamma_i b
+        let b = gammas.iter().fold(Integer::new(), |b, gamma| b + gamma) * &pp.b;
+        Self::eval_equations(pp, &c, z, &y, &b, proof)
+    }
+
+    // Binds the folding challenges to the parameters and the statement, and derives `gamma_0 = 1`
+    // and a 128-bit `gamma_i` for every further commitment. Short challenges keep the coefficients
+    // of the folded polynomial small.
+    fn batch_challenges(pp: &PP<G, T>, cs: &[G::Elem], z: &Integer, ys: &[Integer]) -> Vec<Integer> {
+        let mut transcript = Transcript::<G>::new(b"dark.eval_batch");
+        transcript.append_elem(b"g", &pp.g);
+        transcript.append_integer(b"q", &pp.q);
+        transcript.append_integer(b"p", &pp.p);
+        transcript.append_integer(b"z", z);
+        transcript.append_u64(b"n", cs.len() as u64);
+        for (c, y) in cs.iter().zip(ys) {
+            transcript.append_elem(b"c", c);
+            transcript.append_integer(b"y", y);
+        }
+        let mut gammas = vec![Integer::from(1)];
+        gammas.extend((1..cs.len()).map(|_| transcript.challenge_bits(b"gamma", 128)));
+        gammas.truncate(cs.len());
+        gammas
+    }
+
+    // Binds the challenges of a multi-point proof to the parameters and the statement.
+    fn multi_transcript(pp: &PP<G, T>, c: &G::Elem, zs: &[Integer], ys: &[Integer]) -> Transcript<G> {
+        let mut transcript = Transcript::new(b"dark.eval_multi");
+        transcript.append_elem(b"g", &pp.g);
+        transcript.append_integer(b"q", &pp.q);
+        transcript.append_integer(b"p", &pp.p);
+        transcript.append_elem(b"c", c);
+        transcript.append_u64(b"n", zs.len() as u64);
+        for (z, y) in zs.iter().zip(ys) {
+            transcript.append_integer(b"z", z);
+            transcript.append_integer(b"y", y);
+        }
+        transcript
+    }
+
+    // Absorbs the quotient commitment and derives the point `r` in `[0, p)` the identity is
+    // checked at.
+    fn multi_point(pp: &PP<G, T>, transcript: &mut Transcript<G>, c_h: &G::Elem) -> Integer {
+        transcript.append_elem(b"c_h", c_h);
+        let bits = pp.p.significant_bits() + 128;
+        reduce(transcript.challenge_bits(b"r", bits), &pp.p)
+    }
+
+    // Binds the challenges to the public parameters and the statement being proven.
+    fn eval_transcript(
+        pp: &PP<G, T>,
+        c: &G::Elem,
+        z: &Integer,
+        y: &Integer,
+        d: usize,
+    ) -> Transcript<G> {
+        let mut transcript = Transcript::new(b"dark.eval");
+        transcript.append_elem(b"g", &pp.g);
+        transcript.append_integer(b"q", &pp.q);
+        transcript.append_integer(b"p", &pp.p);
+        transcript.append_elem(b"c", c);
+        transcript.append_integer(b"z", z);
+        transcript.append_integer(b"y", y);
+        transcript.append_u64(b"d", d as u64);
+        transcript
+    }
+
+    // Absorbs the halves of a round, which together with the earlier messages fix the statement
+    // `C_R^(q^m) = C / C_L` of the round's PoE, whose challenge is drawn next.
+    fn absorb_halves(transcript: &mut Transcript<G>, c_l: &G::Elem, c_r: &G::Elem, y_l: &Integer, y_r: &Integer) {
+        transcript.append_elem(b"c_l", c_l);
+        transcript.append_elem(b"c_r", c_r);
+        transcript.append_integer(b"y_l", y_l);
+        transcript.append_integer(b"y_r", y_r);
+    }
+
+    // Absorbs the round's PoE and derives the folding challenge `alpha` in `[0, p)`. Squeezing
+    // 128 bits more than `p` has makes `alpha` statistically close to uniform.
+    fn eval_challenge(pp: &PP<G, T>, transcript: &mut Transcript<G>, poe: &PoE<G>) -> Integer {
+        transcript.append_elem(b"poe", &poe.Q);
+        let bits = pp.p.significant_bits() + 128;
+        reduce(transcript.challenge_bits(b"alpha", bits), &pp.p)
+    }
+}
+
+/// Commitments are homomorphic: `C(f) * C(g) = C(f + g)`, `C(f)^k = C(k f)` and
+/// `C(f)^(q^k) = C(X^k f)`, since the encoding `f(q)` is linear in `f`.
+///
+/// The coefficients of the combined polynomials can outgrow `pp.b`, in which case the result is
+/// still a commitment to them, but no opening or evaluation proof for it will verify.
+impl<G: UnknownOrderGroup, T> PolyComm<G, T> {
+    /// Returns the commitment to `k f` for the polynomial `f` committed to by `self`.
+    ///
+    /// Panics if `k` is negative and `self` is not a valid element, as `G::exp` has no inverse to
+    /// raise to `-k` then; use `try_scale` for commitments from untrusted sources.
+    pub fn scale(&self, k: &Integer) -> PolyComm<G, T> {
+        self.scale_unchecked(k).expect("the commitment is not invertible")
+    }
+
+    /// Same as `scale`, but fails with `Error::InvalidElement` instead of panicking if `self` is
+    /// not a valid group element.
+    pub fn try_scale(&self, k: &Integer) -> Result<PolyComm<G, T>> {
+        G::validate(&self.c)?;
+        self.scale_unchecked(k).ok_or(Error::InvalidElement)
+    }
+
+    // Raises the commitment to `k` without validating it first.
+    fn scale_unchecked(&self, k: &Integer) -> Option<PolyComm<G, T>> {
+        G::exp(&self.c, k).map(|c| PolyComm { phantom: PhantomData, c })
+    }
+
+    /// Returns the commitment to `X^k f` for the polynomial `f` committed to by `self`, i.e.
+    /// shifts the coefficients up by `k` places. The degree grows by `k`.
+    pub fn shift(&self, pp: &PP<G, T>, k: u32) -> PolyComm<G, T> {
+        self.scale(&pp.q.clone().pow(k))
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Add for &PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn add(self, other: &PolyComm<G, T>) -> PolyComm<G, T> {
+        PolyComm { phantom: PhantomData, c: G::opnew(&self.c, &other.c) }
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Add for PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn add(self, other: PolyComm<G, T>) -> PolyComm<G, T> {
+        &self + &other
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Neg for &PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn neg(self) -> PolyComm<G, T> {
+        PolyComm { phantom: PhantomData, c: G::invnew(&self.c) }
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Neg for PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn neg(self) -> PolyComm<G, T> {
+        -&self
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Sub for &PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn sub(self, other: &PolyComm<G, T>) -> PolyComm<G, T> {
+        PolyComm { phantom: PhantomData, c: G::opnew(&self.c, &G::invnew(&other.c)) }
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Sub for PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn sub(self, other: PolyComm<G, T>) -> PolyComm<G, T> {
+        &self - &other
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Mul<&Integer> for &PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn mul(self, k: &Integer) -> PolyComm<G, T> {
+        self.scale(k)
+    }
+}
+
+impl<G: UnknownOrderGroup, T> Mul<&Integer> for PolyComm<G, T> {
+    type Output = PolyComm<G, T>;
+
+    fn mul(self, k: &Integer) -> PolyComm<G, T> {
+        self.scale(k)
+    }
+}
+
+/// Encodes the polynomial `f` (coefficients in increasing degree order) as the integer `f(q)`.
+///
+/// Coefficients may be negative; as long as every `|f_i| < q/2` the encoding is injective and
+/// `decode` recovers `f` (subsection 4.2 of the paper).
+pub fn encode(f: &[Integer], q: &Integer) -> Integer {
+    // Horner's rule, starting from the leading coefficient.
+    let mut x = Integer::new();
+    for coeff in f.iter().rev() {
+        x *= q;
+        x += coeff;
+    }
+    x
+}
+
+/// Evaluates the polynomial `f` at `z` modulo `p`, returning a value in `[0, p)`.
+pub fn evaluate(f: &[Integer], z: &Integer, p: &Integer) -> Integer {
+    let mut y = Integer::new();
+    for coeff in f.iter().rev() {
+        y = reduce(y * z + coeff, p);
+    }
+    y
+}
+
+// Reduces `x` into `[0, p)`.
+fn reduce(x: Integer, p: &Integer) -> Integer {
+    x.div_rem_euc(p.clone()).1
+}
+
+// Reduces `x` into `[-(p - 1) / 2, (p - 1) / 2]`, for odd `p`.
+fn balance(x: Integer, p: &Integer) -> Integer {
+    let x = reduce(x, p);
+    if Integer::from(&x * 2) > *p {
+        x - p
+    } else {
+        x
+    }
+}
+
+// The base `p^(2 * ceil(log2(d_max + 1)) + 1)` at which polynomials of degree `d_max` are
+// encoded: every halving round of the evaluation protocol can grow the coefficients by a factor
+// of `p`, and `q` must stay large enough for the grown polynomials to decode uniquely.
+fn encoding_base(p: &Integer, d_max: usize) -> Result<Integer> {
+    let bound = 2 * (((d_max as f64) + 1.0).log2().ceil() as u32) + 1;
+    let q = p.clone().pow(bound);
+    // The bit length of encodings, `(d_max + 1) * bits(q)`, has to fit a `u32`.
+    let max_bits = (d_max as u64).checked_add(1).and_then(|n| n.checked_mul(q.significant_bits() as u64));
+    if !matches!(max_bits, Some(bits) if bits <= u64::from(u32::MAX)) {
+        return Err(Error::DegreeTooLarge);
+    }
+    Ok(q)
+}
+
+// Checks that every equation from `eval_equations` multiplies out to the identity.
+fn check_equations<G: Group>(equations: Vec<Equation<G>>) -> Result<()> {
+    for (bases, exps) in equations {
+        if multi_exp_general::<G>(&bases, &exps) != G::idnew() {
+            return Err(Error::BadProof);
+        }
+    }
+    Ok(())
+}
+
+// The vanishing polynomial `prod_i (X - zs[i])` modulo `p`.
+fn vanishing_poly(zs: &[Integer], p: &Integer) -> Vec<Integer> {
+    let mut v = vec![Integer::from(1)];
+    for z in zs {
+        // v * (X - z), in place from the lowest coefficient up.
+        v.insert(0, Integer::new());
+        for i in 0..v.len() - 1 {
+            let z_v = Integer::from(&v[i + 1] * z);
+            v[i] = reduce(&v[i] - z_v, p);
+        }
+    }
+    v
+}
+
+// Divides `f` by the monic polynomial `m` modulo `p`, returning the quotient and the remainder
+// with coefficients in `[0, p)`.
+fn div_rem_monic(f: &[Integer], m: &[Integer], p: &Integer) -> (Vec<Integer>, Vec<Integer>) {
+    let mut rem: Vec<Integer> = f.iter().map(|coeff| reduce(coeff.clone(), p)).collect();
+    let deg = m.len() - 1;
+    if rem.len() <= deg {
+        return (Vec::new(), rem);
+    }
+    let mut quo = vec![Integer::new(); rem.len() - deg];
+    for i in (0..quo.len()).rev() {
+        // Cancel the leading coefficient `rem[i + deg]` with `rem[i + deg] X^i m`.
+        quo[i] = rem[i + deg].clone();
+        for (j, m_j) in m.iter().enumerate() {
+            let q_m = Integer::from(&quo[i] * m_j);
+            rem[i + j] = reduce(&rem[i + j] - q_m, p);
+        }
     }
+    rem.truncate(deg);
+    (quo, rem)
 }
 
-#[cfg(test)]
+// Evaluates the polynomial of degree below `zs.len()` through the points `(zs[i], ys[i])` at `r`,
+// modulo `p`. Returns `None` if two of the points coincide modulo `p`.
+fn interpolate_at(zs: &[Integer], ys: &[Integer], r: &Integer, p: &Integer) -> Option<Integer> {
+    let mut y = Integer::new();
+    for (i, (z_i, y_i)) in zs.iter().zip(ys).enumerate() {
+        // y_i * prod_{j != i} (r - z_j) / (z_i - z_j)
+        let mut num = y_i.clone();
+        let mut den = Integer::from(1);
+        for (j, z_j) in zs.iter().enumerate() {
+            if i != j {
+                num = reduce(num * Integer::from(r - z_j), p);
+                den = reduce(den * Integer::from(z_i - z_j), p);
+            }
+        }
+        let den_inv = den.invert(p).ok()?;
+        y = reduce(y + num * den_inv, p);
+    }
+    Some(y)
+}
+
+/// Decodes `x` into the unique polynomial of degree `d` whose balanced base-`q` digits are the
+/// coefficients, i.e. `f_i` lies in `(-q/2, q/2]` for `i < d`.
+///
+/// Whatever remains after the first `d` digits becomes the leading coefficient, so
+/// `encode(&decode(x, q, d), q) == x` holds for every `x`, and an out-of-range leading
+/// coefficient signals that `x` is not the encoding of a degree-`d` polynomial.
+pub fn decode(mut x: Integer, q: &Integer, d: usize) -> Vec<Integer> {
+    let mut f = Vec::with_capacity(d + 1);
+    for _ in 0..d {
+        let (quo, mut rem) = x.div_rem_euc(q.clone());
+        x = quo;
+        // Move the digit into the balanced range, carrying into the next one.
+        if Integer::from(&rem * 2) > *q {
+            rem -= q;
+            x += 1;
+        }
+        f.push(rem);
+    }
+    f.push(x);
+    f
+}
+
+#[cfg(all(test, feature = "class_group_sti"))]
 mod tests {
     use super::*;
-    use crate::groups::classgroupsti::ClassyGroup;
+    use crate::groups::Group;
+    use crate::groups::classgroupsti::{ClassyGroup, Mpz};
+    use rug::integer::Order;
+    use std::str::FromStr;
 
     #[test]
     fn test_poly_setup() {
@@ -69,4 +989,707 @@ mod tests {
         let _pp = PolyComm::<ClassyGroup, Integer>::setup(_lambda, d_max);
         
     }
+
+    #[test]
+    fn test_setup_from_seed() {
+        let seed = b"supersonic test seed";
+        let pp_1 = PolyComm::<ClassyGroup, Integer>::setup_from_seed(seed, 2048, 4);
+        let pp_2 = PolyComm::<ClassyGroup, Integer>::setup_from_seed(seed, 2048, 4);
+        assert_eq!(pp_1, pp_2);
+        assert!(pp_1.verify_derivation(seed));
+        assert_ne!(pp_1.g, ClassyGroup::unknown_order_elem());
+
+        // Any change to the inputs yields unrelated parameters.
+        let other_seed = PolyComm::<ClassyGroup, Integer>::setup_from_seed(b"other seed", 2048, 4);
+        let other_degree = PolyComm::<ClassyGroup, Integer>::setup_from_seed(seed, 2048, 5);
+        for pp in &[other_seed, other_degree] {
+            assert_ne!(pp.g, pp_1.g);
+            assert_ne!(pp.p, pp_1.p);
+        }
+        assert!(!pp_1.verify_derivation(b"other seed"));
+    }
+
+    #[test]
+    fn test_verify_derivation_tampered() {
+        let seed = b"supersonic test seed";
+        let pp = PolyComm::<ClassyGroup, Integer>::setup_from_seed(seed, 2048, 4);
+
+        let mut bad_g = pp.clone();
+        bad_g.g = ClassyGroup::unknown_order_elem();
+        assert!(!bad_g.verify_derivation(seed));
+
+        let mut bad_p = pp.clone();
+        bad_p.p = bad_p.p.next_prime();
+        assert!(!bad_p.verify_derivation(seed));
+
+        let mut bad_q = pp.clone();
+        bad_q.q *= &pp.p;
+        assert!(!bad_q.verify_derivation(seed));
+
+        let mut bad_degree = pp;
+        bad_degree.d_max += 1;
+        assert!(!bad_degree.verify_derivation(seed));
+    }
+
+    #[test]
+    fn test_commit_homomorphic() {
+        let d_max = 4;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let bound = Integer::from(&pp.p - 1) / 2;
+        let f = random_poly(d_max, &bound);
+        let g = random_poly(d_max, &bound);
+        let sum: Vec<Integer> = f.iter().zip(g.iter()).map(|(a, b)| Integer::from(a + b)).collect();
+
+        let comm_f = PolyComm::commit(&pp, &f);
+        let comm_g = PolyComm::commit(&pp, &g);
+        let comm_sum = PolyComm::commit(&pp, &sum);
+        assert_eq!(ClassyGroup::opnew(&comm_f.c, &comm_g.c), comm_sum.c);
+        assert_ne!(comm_f, comm_g);
+    }
+
+    #[test]
+    fn test_homomorphic_ops() {
+        let d_max = 5;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let bound = Integer::from(&pp.b / 4);
+        let f = random_poly(3, &bound);
+        let g = random_poly(2, &bound);
+        let (comm_f, comm_g) = (PolyComm::commit(&pp, &f), PolyComm::commit(&pp, &g));
+        let combine = |k: i32| -> Vec<Integer> {
+            let mut h = f.clone();
+            for (h_i, g_i) in h.iter_mut().zip(g.iter()) {
+                *h_i += Integer::from(g_i * k);
+            }
+            h
+        };
+
+        assert_eq!(&comm_f + &comm_g, PolyComm::commit(&pp, &combine(1)));
+        assert_eq!(&comm_f - &comm_g, PolyComm::commit(&pp, &combine(-1)));
+        assert_eq!(comm_f.clone() + comm_g.clone() - comm_g.clone(), comm_f);
+        let neg_g: Vec<Integer> = g.iter().map(|g_i| Integer::from(-g_i)).collect();
+        assert_eq!(-&comm_g, PolyComm::commit(&pp, &neg_g));
+        assert_eq!(-(-comm_g.clone()), comm_g);
+
+        for k in &[0, 1, 3, -2] {
+            let k = Integer::from(*k);
+            let k_f: Vec<Integer> = f.iter().map(|f_i| Integer::from(f_i * &k)).collect();
+            assert_eq!(&comm_f * &k, PolyComm::commit(&pp, &k_f));
+            assert_eq!(comm_f.scale(&k), comm_f.clone() * &k);
+            assert_eq!(comm_f.try_scale(&k), Ok(comm_f.scale(&k)));
+        }
+
+        for k in 0..3 {
+            let mut shifted = vec![Integer::new(); k as usize];
+            shifted.extend(f.iter().cloned());
+            let comm = comm_f.shift(&pp, k);
+            assert_eq!(comm, PolyComm::commit(&pp, &shifted));
+            assert!(PolyComm::verify_open(&pp, &comm, &shifted));
+        }
+
+        // Combinations open to the combined polynomial as long as it is within the bounds.
+        let h = combine(2);
+        assert!(PolyComm::verify_open(&pp, &(&comm_f + &(&comm_g * &Integer::from(2))), &h));
+        assert!(!PolyComm::verify_open(&pp, &comm_f.shift(&pp, 3), &[vec![Integer::new(); 3], f].concat()));
+    }
+
+    #[test]
+    fn test_open_verify() {
+        let d_max = 4;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+        assert!(PolyComm::verify_open(&pp, &comm, &PolyComm::<ClassyGroup, Integer>::open(&f)));
+
+        // Lower degree polynomials open as well.
+        let low = random_poly(1, &pp.b);
+        let low_comm = PolyComm::commit(&pp, &low);
+        assert!(PolyComm::verify_open(&pp, &low_comm, &low));
+
+        let mut wrong = f.clone();
+        wrong[0] += 1;
+        assert!(!PolyComm::verify_open(&pp, &comm, &wrong));
+    }
+
+    #[test]
+    fn test_open_rejects_out_of_bound_coeffs() {
+        let d_max = 2;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max - 1, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+
+        // `f + q - X` has the same encoding, hence the same commitment.
+        let mut alias = f.clone();
+        alias[0] += &pp.q;
+        alias[1] -= 1;
+        assert_eq!(PolyComm::commit(&pp, &alias), comm);
+        assert!(!PolyComm::verify_open(&pp, &comm, &alias));
+
+        let edge = vec![pp.b.clone(), Integer::from(-&pp.b)];
+        let edge_comm = PolyComm::commit(&pp, &edge);
+        assert!(PolyComm::verify_open(&pp, &edge_comm, &edge));
+
+        let over = vec![Integer::from(&pp.b + 1)];
+        let over_comm = PolyComm::commit(&pp, &over);
+        assert!(!PolyComm::verify_open(&pp, &over_comm, &over));
+    }
+
+    #[test]
+    fn test_open_rejects_high_degree() {
+        let d_max = 2;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = vec![Integer::from(1); d_max + 2];
+        let comm = PolyComm::commit(&pp, &f);
+        assert!(!PolyComm::verify_open(&pp, &comm, &f));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // 3 - 2X + X^2 at X = 5 is 18.
+        let f = vec![Integer::from(3), Integer::from(-2), Integer::from(1)];
+        assert_eq!(evaluate(&f, &Integer::from(5), &Integer::from(7)), 4);
+        assert_eq!(evaluate(&f, &Integer::from(-5), &Integer::from(101)), 38);
+        assert_eq!(evaluate(&[], &Integer::from(5), &Integer::from(7)), 0);
+    }
+
+    #[test]
+    fn test_eval_proof() {
+        let d_max = 6;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        // Covers coefficient counts that are and are not powers of two.
+        for d in 0..=d_max {
+            let f = random_poly(d, &pp.b);
+            let comm = PolyComm::commit(&pp, &f);
+            let z = random_poly(0, &pp.b).remove(0);
+            let y = evaluate(&f, &z, &pp.p);
+            let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+            assert_eq!(proof.d, d);
+            assert!(PolyComm::eval_verify(&pp, &comm, &z, &y, &proof));
+        }
+    }
+
+    #[test]
+    fn test_eval_proof_rejects_wrong_statement() {
+        let d_max = 5;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+        let z = Integer::from(12345);
+        let y = evaluate(&f, &z, &pp.p);
+        let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+        assert!(PolyComm::eval_verify(&pp, &comm, &z, &y, &proof));
+
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &Integer::from(&y + 1), &proof));
+        assert!(!PolyComm::eval_verify(&pp, &comm, &Integer::from(&z + 1), &y, &proof));
+
+        let other = PolyComm::commit(&pp, &random_poly(d_max, &pp.b));
+        assert!(!PolyComm::eval_verify(&pp, &other, &z, &y, &proof));
+    }
+
+    #[test]
+    fn test_eval_proof_rejects_tampering() {
+        let d_max = 4;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+        let z = Integer::from(-7);
+        let y = evaluate(&f, &z, &pp.p);
+        let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+
+        let mut bad = proof.clone();
+        bad.f0 += 1;
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &y, &bad));
+
+        let mut bad = proof.clone();
+        bad.rounds[0].y_l += 1;
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &y, &bad));
+
+        let mut bad = proof.clone();
+        bad.rounds[1].c_r = ClassyGroup::opnew(&bad.rounds[1].c_r, &pp.g);
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &y, &bad));
+
+        let mut bad = proof.clone();
+        bad.rounds[0].poe.Q = ClassyGroup::opnew(&bad.rounds[0].poe.Q, &pp.g);
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &y, &bad));
+
+        let mut bad = proof.clone();
+        bad.rounds.pop();
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &y, &bad));
+
+        let mut bad = proof;
+        bad.d = d_max + 1;
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &y, &bad));
+    }
+
+    #[test]
+    fn test_eval_proof_rejects_unbounded_poly() {
+        // A polynomial with an oversized coefficient cannot be proven, even though its
+        // evaluation is correct.
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, 1);
+        let f = vec![Integer::from(&pp.q * 3), Integer::from(1)];
+        let comm = PolyComm::commit(&pp, &f);
+        let z = Integer::from(3);
+        let y = evaluate(&f, &z, &pp.p);
+        let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+        assert!(!PolyComm::eval_verify(&pp, &comm, &z, &y, &proof));
+    }
+
+    // Proves evaluations of random polynomials of the given degrees.
+    fn eval_claims(pp: &PP<ClassyGroup, Integer>, degrees: &[usize]) -> Vec<EvalClaim<ClassyGroup, Integer>> {
+        degrees
+            .iter()
+            .map(|&d| {
+                let f = random_poly(d, &pp.b);
+                let z = Integer::from(d * 31) - 50;
+                let proof = PolyComm::eval_prove(pp, &f, &z, &pp.p);
+                (PolyComm::commit(pp, &f), z.clone(), evaluate(&f, &z, &pp.p), proof)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_eval_verify() {
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, 4);
+        let claims = eval_claims(&pp, &[0, 1, 2, 3, 4]);
+        assert!(PolyComm::batch_eval_verify(&pp, &claims));
+        assert!(PolyComm::batch_eval_verify(&pp, &claims[..1]));
+        assert!(PolyComm::batch_eval_verify(&pp, &[]));
+        // The same claim twice is fine.
+        assert!(PolyComm::batch_eval_verify(&pp, &[claims[2].clone(), claims[2].clone()]));
+    }
+
+    #[test]
+    fn test_batch_eval_verify_catches_one_bad_proof() {
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, 4);
+        let claims = eval_claims(&pp, &[0, 1, 2, 3, 4]);
+        let with = |i: usize, tamper: &dyn Fn(&mut EvalClaim<ClassyGroup, Integer>)| {
+            let mut batch = claims.clone();
+            tamper(&mut batch[i]);
+            batch
+        };
+
+        // Each of these only fails a group equation, which is all the batch combines.
+        let bad_q = with(3, &|claim| claim.3.rounds[1].poe.Q = ClassyGroup::opnew(&claim.3.rounds[1].poe.Q, &pp.g));
+        let bad_f0 = with(4, &|claim| claim.3.f0 += &pp.p);
+        let bad_comm = with(1, &|claim| claim.0.c = ClassyGroup::opnew(&claim.0.c, &pp.g));
+        for batch in &[bad_q, bad_f0, bad_comm] {
+            assert_eq!(PolyComm::try_batch_eval_verify(&pp, batch), Err(Error::BadProof));
+        }
+        let bad_y = with(2, &|claim| claim.2 += 1);
+        assert!(!PolyComm::batch_eval_verify(&pp, &bad_y));
+
+        // Errors of `g^p` and `g^-p` would cancel out in a plain product of the equations.
+        let mut cancelling = with(3, &|claim| claim.3.f0 += &pp.p);
+        cancelling[4].3.f0 -= &pp.p;
+        assert!(!PolyComm::eval_verify(&pp, &cancelling[3].0, &cancelling[3].1, &cancelling[3].2, &cancelling[3].3));
+        assert!(!PolyComm::batch_eval_verify(&pp, &cancelling));
+    }
+
+    #[test]
+    fn test_batch_eval_verify_odd_degree() {
+        // Degree 6 has an odd number of coefficients in the first round and degree 4 in the
+        // first two, where the verifier leaves the power `q` of the commitment to the batch.
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, 6);
+        let claims = eval_claims(&pp, &[4, 6]);
+        assert!(PolyComm::batch_eval_verify(&pp, &claims));
+
+        for (i, round) in [(0, 0), (0, 1), (1, 0)] {
+            let mut batch = claims.clone();
+            let c_l = &mut batch[i].3.rounds[round].c_l;
+            *c_l = ClassyGroup::opnew(c_l, &pp.g);
+            assert_eq!(PolyComm::try_batch_eval_verify(&pp, &batch), Err(Error::BadProof));
+        }
+    }
+
+    #[test]
+    fn test_poly_mod_p() {
+        let p = Integer::from(101);
+        let zs: Vec<Integer> = [3, -4, 50, 7].iter().map(|&z| Integer::from(z)).collect();
+        let v = vanishing_poly(&zs, &p);
+        assert_eq!(v.len(), zs.len() + 1);
+        assert!(zs.iter().all(|z| evaluate(&v, z, &p) == 0));
+
+        let f = random_poly(9, &Integer::from(1000));
+        let (quo, rem) = div_rem_monic(&f, &v, &p);
+        assert_eq!(quo.len(), 6);
+        assert_eq!(rem.len(), 4);
+        for x in 0..20 {
+            let x = Integer::from(x);
+            let prod = Integer::from(&evaluate(&quo, &x, &p) * &evaluate(&v, &x, &p));
+            assert_eq!(evaluate(&f, &x, &p), reduce(prod + evaluate(&rem, &x, &p), &p));
+        }
+        assert_eq!(div_rem_monic(&f[..2], &v, &p).0, Vec::<Integer>::new());
+
+        // The remainder is the interpolation of `f` at the roots of `v`.
+        let ys: Vec<Integer> = zs.iter().map(|z| evaluate(&f, z, &p)).collect();
+        for r in 0..20 {
+            let r = Integer::from(r);
+            assert_eq!(interpolate_at(&zs, &ys, &r, &p), Some(evaluate(&rem, &r, &p)));
+        }
+        let twice = [zs[0].clone(), Integer::from(&zs[0] + &p)];
+        assert_eq!(interpolate_at(&twice, &ys[..2], &Integer::new(), &p), None);
+        assert_eq!(balance(Integer::from(51), &p), -50);
+        assert_eq!(balance(Integer::from(-50), &p), -50);
+    }
+
+    #[test]
+    fn test_eval_proof_multi() {
+        let d_max = 4;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+        // Fewer, as many, and more points than coefficients.
+        for &n in &[0, 1, 3, 5, 7] {
+            let zs: Vec<Integer> = (0..n).map(|i| Integer::from(i * i) - 9).collect();
+            let ys: Vec<Integer> = zs.iter().map(|z| evaluate(&f, z, &pp.p)).collect();
+            let proof = PolyComm::eval_prove_multi(&pp, &f, &zs, &pp.p);
+            assert_eq!(PolyComm::try_eval_verify_multi(&pp, &comm, &zs, &ys, &proof), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_eval_proof_multi_rejects_wrong_statement() {
+        let d_max = 3;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+        let zs: Vec<Integer> = [2, 3, 5].iter().map(|&z| Integer::from(z)).collect();
+        let ys: Vec<Integer> = zs.iter().map(|z| evaluate(&f, z, &pp.p)).collect();
+        let proof = PolyComm::eval_prove_multi(&pp, &f, &zs, &pp.p);
+        assert!(PolyComm::eval_verify_multi(&pp, &comm, &zs, &ys, &proof));
+
+        let mut wrong_y = ys.clone();
+        wrong_y[1] += 1;
+        assert!(!PolyComm::eval_verify_multi(&pp, &comm, &zs, &wrong_y, &proof));
+        assert!(!PolyComm::eval_verify_multi(&pp, &comm, &zs[..2], &ys[..2], &proof));
+        assert!(!PolyComm::eval_verify_multi(&pp, &comm, &zs, &ys[..2], &proof));
+        let swapped = [zs[1].clone(), zs[0].clone(), zs[2].clone()];
+        assert!(!PolyComm::eval_verify_multi(&pp, &comm, &swapped, &ys, &proof));
+        let other = PolyComm::commit(&pp, &random_poly(d_max, &pp.b));
+        assert!(!PolyComm::eval_verify_multi(&pp, &other, &zs, &ys, &proof));
+
+        let mut bad = proof.clone();
+        bad.y_h += 1;
+        assert!(!PolyComm::eval_verify_multi(&pp, &comm, &zs, &ys, &bad));
+        let mut bad = proof.clone();
+        bad.c_h = ClassyGroup::opnew(&bad.c_h, &pp.g);
+        assert!(!PolyComm::eval_verify_multi(&pp, &comm, &zs, &ys, &bad));
+        let mut bad = proof;
+        bad.eval.f0 += &pp.p;
+        assert!(!PolyComm::eval_verify_multi(&pp, &comm, &zs, &ys, &bad));
+    }
+
+    #[test]
+    fn test_eval_proof_batch() {
+        let d_max = 4;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let fs: Vec<Vec<Integer>> = (0..=d_max).rev().map(|d| random_poly(d, &pp.b)).collect();
+        let comms: Vec<_> = fs.iter().map(|f| PolyComm::commit(&pp, f)).collect();
+        let z = Integer::from(-23);
+        let ys: Vec<Integer> = fs.iter().map(|f| evaluate(f, &z, &pp.p)).collect();
+
+        let proof = PolyComm::eval_prove_batch(&pp, &fs, &z, &pp.p);
+        assert_eq!(PolyComm::try_eval_verify_batch(&pp, &comms, &z, &ys, &proof), Ok(()));
+        // Without folding, the batch of one is a plain evaluation proof.
+        let single = PolyComm::eval_prove_batch(&pp, &fs[1..2], &z, &pp.p);
+        assert_eq!(single, PolyComm::eval_prove(&pp, &fs[1], &z, &pp.p));
+        assert!(PolyComm::eval_verify(&pp, &comms[1], &z, &ys[1], &single));
+        let empty = PolyComm::<ClassyGroup, Integer>::eval_prove_batch(&pp, &[], &z, &pp.p);
+        assert!(PolyComm::eval_verify_batch(&pp, &[], &z, &[], &empty));
+    }
+
+    #[test]
+    fn test_eval_proof_batch_rejects_wrong_statement() {
+        let d_max = 3;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let fs: Vec<Vec<Integer>> = (0..3).map(|_| random_poly(d_max, &pp.b)).collect();
+        let comms: Vec<_> = fs.iter().map(|f| PolyComm::commit(&pp, f)).collect();
+        let z = Integer::from(77);
+        let ys: Vec<Integer> = fs.iter().map(|f| evaluate(f, &z, &pp.p)).collect();
+        let proof = PolyComm::eval_prove_batch(&pp, &fs, &z, &pp.p);
+        assert!(PolyComm::eval_verify_batch(&pp, &comms, &z, &ys, &proof));
+
+        for i in 0..3 {
+            let mut wrong_y = ys.clone();
+            wrong_y[i] += 1;
+            assert!(!PolyComm::eval_verify_batch(&pp, &comms, &z, &wrong_y, &proof));
+        }
+        let mut swapped = comms.clone();
+        swapped.swap(0, 2);
+        assert!(!PolyComm::eval_verify_batch(&pp, &swapped, &z, &ys, &proof));
+        assert!(!PolyComm::eval_verify_batch(&pp, &comms[..2], &z, &ys[..2], &proof));
+        assert!(!PolyComm::eval_verify_batch(&pp, &comms, &z, &ys[..2], &proof));
+        assert!(!PolyComm::eval_verify_batch(&pp, &comms, &Integer::from(78), &ys, &proof));
+
+        // A polynomial with an oversized coefficient cannot hide in the batch.
+        let mut big = fs.clone();
+        big[2][0] += &pp.q;
+        let big_comms: Vec<_> = big.iter().map(|f| PolyComm::commit(&pp, f)).collect();
+        let big_ys: Vec<Integer> = big.iter().map(|f| evaluate(f, &z, &pp.p)).collect();
+        let big_proof = PolyComm::eval_prove_batch(&pp, &big, &z, &pp.p);
+        assert!(!PolyComm::eval_verify_batch(&pp, &big_comms, &z, &big_ys, &big_proof));
+    }
+
+    #[test]
+    fn test_degree_bound() {
+        let d_max = 3;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        for d in 0..=d_max {
+            let f = random_poly(d, &pp.b);
+            let comm = PolyComm::commit(&pp, &f);
+            for bound in d..=d_max {
+                let proof = PolyComm::prove_degree_bound(&pp, &f, bound);
+                assert_eq!(PolyComm::try_verify_degree_bound(&pp, &comm, bound, &proof), Ok(()));
+                if bound > 0 {
+                    assert!(!PolyComm::verify_degree_bound(&pp, &comm, bound - 1, &proof));
+                }
+            }
+        }
+        let zero = PolyComm::commit(&pp, &[]);
+        let proof = PolyComm::prove_degree_bound(&pp, &[], 0);
+        assert!(PolyComm::verify_degree_bound(&pp, &zero, 0, &proof));
+        assert_eq!(
+            PolyComm::try_verify_degree_bound(&pp, &zero, d_max + 1, &proof),
+            Err(Error::DegreeTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_degree_bound_rejects_high_degree() {
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, 5);
+        let f = random_poly(4, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+
+        // A proof for the low coefficients of `f` is not a proof for `f`, nor is a tampered one.
+        let low = PolyComm::prove_degree_bound(&pp, &f[..3], 2);
+        assert!(!PolyComm::verify_degree_bound(&pp, &comm, 2, &low));
+        let mut wrong_y = PolyComm::prove_degree_bound(&pp, &f, 4);
+        wrong_y.y += 1;
+        assert!(!PolyComm::verify_degree_bound(&pp, &comm, 4, &wrong_y));
+
+        // Folding the high coefficients into the top one of a degree 2 polynomial makes it
+        // oversized, which the final bound check catches.
+        let mut folded = f[..3].to_vec();
+        folded[2] += Integer::from(&f[3] * &pp.q) + &f[4] * pp.q.clone().pow(2);
+        assert_eq!(PolyComm::commit(&pp, &folded), comm);
+        let proof = PolyComm::prove_degree_bound(&pp, &folded, 2);
+        assert!(PolyComm::try_verify_degree_bound(&pp, &comm, 2, &proof).is_err());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let d_max = 4;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup_from_seed(b"serde", 2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+        let z = Integer::from(-17);
+        let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+
+        let pp_2: PP<ClassyGroup, Integer> =
+            bincode::deserialize(&bincode::serialize(&pp).unwrap()).unwrap();
+        let comm_2: PolyComm<ClassyGroup, Integer> =
+            bincode::deserialize(&bincode::serialize(&comm).unwrap()).unwrap();
+        let proof_2: EvalProof<ClassyGroup> =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+        assert_eq!(pp_2, pp);
+        assert_eq!(comm_2, comm);
+        assert_eq!(proof_2, proof);
+        assert!(pp_2.verify_derivation(b"serde"));
+        assert!(PolyComm::eval_verify(&pp_2, &comm_2, &z, &evaluate(&f, &z, &pp.p), &proof_2));
+
+        let zs = vec![Integer::from(1), Integer::from(2)];
+        let multi = PolyComm::eval_prove_multi(&pp, &f, &zs, &pp.p);
+        let multi_2: MultiEvalProof<ClassyGroup> =
+            bincode::deserialize(&bincode::serialize(&multi).unwrap()).unwrap();
+        assert_eq!(multi_2, multi);
+
+        let degree = PolyComm::prove_degree_bound(&pp, &f, d_max);
+        let degree_2: DegreeProof<ClassyGroup> =
+            bincode::deserialize(&bincode::serialize(&degree).unwrap()).unwrap();
+        assert_eq!(degree_2, degree);
+    }
+
+    #[test]
+    fn test_serde_rejects_bad_input() {
+        let pp = PolyComm::<ClassyGroup, Integer>::setup_from_seed(b"serde", 2048, 2);
+        let comm = PolyComm::commit(&pp, &[Integer::from(1), Integer::from(2)]);
+        let bytes = bincode::serialize(&comm).unwrap();
+
+        // The version comes first.
+        let mut bad_version = bytes.clone();
+        bad_version[0] ^= 1;
+        type Comm = PolyComm<ClassyGroup, Integer>;
+        assert!(bincode::deserialize::<Comm>(&bad_version).is_err());
+
+        // The last byte belongs to `b`, and flipping its parity leaves no `c` that would give the
+        // form the right discriminant.
+        let mut bad_elem = bytes.clone();
+        *bad_elem.last_mut().unwrap() ^= 1;
+        assert!(bincode::deserialize::<Comm>(&bad_elem).is_err());
+        assert!(bincode::deserialize::<Comm>(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_commit_with_table() {
+        let d_max = 8;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let table = pp.fixed_base_table(1 << 12);
+        for d in 0..=d_max {
+            let f = random_poly(d, &pp.b);
+            assert_eq!(PolyComm::commit_with_table(&pp, &table, &f), PolyComm::commit(&pp, &f));
+        }
+    }
+
+    #[test]
+    fn test_try_setup() {
+        assert!(PolyComm::<ClassyGroup, Integer>::try_setup_from_seed(b"seed", 2048, 4).is_ok());
+        let huge = PolyComm::<ClassyGroup, Integer>::try_setup_from_seed(b"seed", 2048, usize::MAX);
+        assert_eq!(huge, Err(Error::DegreeTooLarge));
+        let huge = PolyComm::<ClassyGroup, Integer>::try_setup(2048, u32::MAX as usize);
+        assert_eq!(huge, Err(Error::DegreeTooLarge));
+        // The group fixes `lambda`.
+        for lambda in &[128, 256, 2047] {
+            let other = PolyComm::<ClassyGroup, Integer>::try_setup_from_seed(b"seed", *lambda, 4);
+            assert_eq!(other, Err(Error::UnsupportedLambda));
+        }
+    }
+
+    #[test]
+    fn test_try_commit_and_open() {
+        let d_max = 2;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::try_commit(&pp, &f).unwrap();
+        assert_eq!(comm, PolyComm::commit(&pp, &f));
+        assert_eq!(PolyComm::try_verify_open(&pp, &comm, &f), Ok(()));
+
+        let mut wrong = f.clone();
+        wrong[0] += 1;
+        assert_eq!(PolyComm::try_verify_open(&pp, &comm, &wrong), Err(Error::BadProof));
+        let high = random_poly(d_max + 1, &pp.b);
+        assert_eq!(PolyComm::try_commit(&pp, &high), Err(Error::DegreeTooLarge));
+        assert_eq!(PolyComm::try_verify_open(&pp, &comm, &high), Err(Error::DegreeTooLarge));
+        let over = vec![Integer::from(&pp.b + 1)];
+        assert_eq!(PolyComm::try_commit(&pp, &over), Err(Error::CoefficientOutOfBound));
+    }
+
+    #[test]
+    fn test_try_eval_verify() {
+        let d_max = 3;
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, d_max);
+        let f = random_poly(d_max, &pp.b);
+        let comm = PolyComm::commit(&pp, &f);
+        let z = Integer::from(5);
+        let y = evaluate(&f, &z, &pp.p);
+        let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+        assert_eq!(PolyComm::try_eval_verify(&pp, &comm, &z, &y, &proof), Ok(()));
+
+        let wrong_y = Integer::from(&y + 1);
+        assert_eq!(PolyComm::try_eval_verify(&pp, &comm, &z, &wrong_y, &proof), Err(Error::BadProof));
+        let mut bad = proof.clone();
+        bad.d = d_max + 1;
+        assert_eq!(PolyComm::try_eval_verify(&pp, &comm, &z, &y, &bad), Err(Error::DegreeTooLarge));
+        let mut bad = proof.clone();
+        bad.f0 = Integer::from(&pp.q * &pp.q);
+        assert_eq!(PolyComm::try_eval_verify(&pp, &comm, &z, &y, &bad), Err(Error::CoefficientOutOfBound));
+
+        // A form of another discriminant, as a malicious prover could send it.
+        let mut invalid = proof.rounds[0].c_l.clone();
+        let c: Integer = Integer::from_str(&invalid.c.to_string()).unwrap() + 1;
+        invalid.c = Mpz::from_str(&c.to_string()).unwrap();
+        let mut bad = proof;
+        bad.rounds[0].c_l = invalid.clone();
+        assert_eq!(PolyComm::try_eval_verify(&pp, &comm, &z, &y, &bad), Err(Error::InvalidElement));
+        let bad_comm = PolyComm { phantom: PhantomData, c: invalid };
+        assert_eq!(PolyComm::try_verify_open(&pp, &bad_comm, &f), Err(Error::InvalidElement));
+        assert_eq!(bad_comm.try_scale(&Integer::from(2)), Err(Error::InvalidElement));
+    }
+
+    #[test]
+    fn test_try_rejects_invalid_pp() {
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, 2);
+        let f = vec![Integer::from(1), Integer::from(2)];
+        let comm = PolyComm::commit(&pp, &f);
+        let z = Integer::from(3);
+        let y = evaluate(&f, &z, &pp.p);
+        let proof = PolyComm::eval_prove(&pp, &f, &z, &pp.p);
+        assert_eq!(pp.validate(), Ok(()));
+
+        let mut bad_q = pp.clone();
+        bad_q.q += 2;
+        assert_eq!(PolyComm::try_commit(&bad_q, &f), Err(Error::InvalidParameters));
+        assert_eq!(PolyComm::try_eval_verify(&bad_q, &comm, &z, &y, &proof), Err(Error::InvalidParameters));
+        let mut bad_b = pp.clone();
+        bad_b.b *= 2;
+        assert_eq!(PolyComm::try_verify_open(&bad_b, &comm, &f), Err(Error::InvalidParameters));
+        let mut bad_p = pp.clone();
+        bad_p.p += 1;
+        assert_eq!(bad_p.validate(), Err(Error::InvalidParameters));
+        let mut bad_degree = pp.clone();
+        bad_degree.d_max = usize::MAX;
+        assert_eq!(PolyComm::try_commit(&bad_degree, &f), Err(Error::DegreeTooLarge));
+        let mut bad_lambda = pp;
+        bad_lambda.lambda = 128;
+        assert_eq!(bad_lambda.validate(), Err(Error::UnsupportedLambda));
+    }
+
+    #[test]
+    fn test_commit_zero_poly() {
+        let pp = PolyComm::<ClassyGroup, Integer>::setup(2048, 2);
+        let zero = vec![Integer::new(); 3];
+        assert_eq!(PolyComm::commit(&pp, &zero).c, ClassyGroup::idnew());
+    }
+
+    // Samples `d + 1` coefficients uniformly from `[-bound, bound]`.
+    fn random_poly(d: usize, bound: &Integer) -> Vec<Integer> {
+        let range: Integer = Integer::from(bound * 2) + 1;
+        (0..=d)
+            .map(|_| {
+                let bytes = rand::thread_rng().gen::<[u8; 32]>();
+                let r = Integer::from_digits(&bytes, Order::Msf);
+                r.div_rem_euc(range.clone()).1 - bound
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let q = (Integer::from(1) << 256) + 297;
+        let bound = Integer::from(&q - 1) / 2;
+        for &d in &[0, 1, 2, 3, 4, 7, 8, 31, 100, 1023, 4096] {
+            let f = random_poly(d, &bound);
+            let x = encode(&f, &q);
+            assert_eq!(decode(x, &q, d), f);
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_extreme_coeffs() {
+        let q = Integer::from(1001);
+        let f: Vec<Integer> = vec![500, -500, 0, -1, 1, 500, -500]
+            .into_iter()
+            .map(Integer::from)
+            .collect();
+        let x = encode(&f, &q);
+        assert_eq!(decode(x, &q, f.len() - 1), f);
+
+        let neg: Vec<Integer> = f.iter().map(|c| Integer::from(-c)).collect();
+        assert_eq!(encode(&neg, &q), -encode(&f, &q));
+    }
+
+    #[test]
+    fn test_encode_small() {
+        // 3 - 2X + X^2 at X = 10
+        let f = vec![Integer::from(3), Integer::from(-2), Integer::from(1)];
+        assert_eq!(encode(&f, &Integer::from(10)), 83);
+        assert_eq!(encode(&[], &Integer::from(10)), 0);
+    }
+
+    #[test]
+    fn test_decode_leading_coeff_absorbs_overflow() {
+        let q = Integer::from(101);
+        let x = Integer::from(7) + Integer::from(1000) * &q;
+        let f = decode(x.clone(), &q, 1);
+        assert_eq!(f, vec![Integer::from(7), Integer::from(1000)]);
+        assert_eq!(encode(&f, &q), x);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,158 @@
+//! Serde support for public parameters, commitments and proofs.
+//!
+//! Integers and group elements are serialized as byte strings in the canonical encodings of
+//! `util::append_integer` and `ElemToBytes`, so the format does not depend on how a backend
+//! represents its elements. Top-level objects start with `VERSION`, and deserializing an object
+//! written with another version fails.
+use crate::error::Error;
+use crate::groups::ElemToBytes;
+use crate::util::{append_integer, read_integer};
+use rug::Integer;
+use serde::de::{Error as _, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Version of the serialization format. Version 2 stores class group elements as `(a, b)` only.
+pub const VERSION: u16 = 2;
+
+/// A top-level object, serialized as the pair `(VERSION, body)`.
+///
+/// The body is the encoding derived with `#[serde(remote = "Self")]`, which leaves the
+/// `Serialize` and `Deserialize` impls to `serialize_versioned` and `deserialize_versioned`.
+pub trait Versioned: Sized {
+  fn serialize_body<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error>;
+  fn deserialize_body<'de, D: Deserializer<'de>>(d: D) -> Result<Self, D::Error>;
+}
+
+/// Serializes `x` behind the format version.
+pub fn serialize_versioned<T: Versioned, S: Serializer>(x: &T, s: S) -> Result<S::Ok, S::Error> {
+  let mut tuple = s.serialize_tuple(2)?;
+  tuple.serialize_element(&VERSION)?;
+  tuple.serialize_element(&Body(x))?;
+  tuple.end()
+}
+
+/// Deserializes an object written by `serialize_versioned`, failing before the body is read if
+/// it was written with another version.
+pub fn deserialize_versioned<'de, T: Versioned, D: Deserializer<'de>>(d: D) -> Result<T, D::Error> {
+  d.deserialize_tuple(2, VersionedVisitor(PhantomData))
+}
+
+struct Body<'a, T>(&'a T);
+
+impl<'a, T: Versioned> Serialize for Body<'a, T> {
+  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+    self.0.serialize_body(s)
+  }
+}
+
+struct OwnedBody<T>(T);
+
+impl<'de, T: Versioned> Deserialize<'de> for OwnedBody<T> {
+  fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+    T::deserialize_body(d).map(OwnedBody)
+  }
+}
+
+struct VersionedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Versioned> Visitor<'de> for VersionedVisitor<T> {
+  type Value = T;
+
+  fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("a format version followed by an object")
+  }
+
+  fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+    let version: u16 = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+    if version != VERSION {
+      return Err(A::Error::custom(Error::Deserialization(format!("unsupported version {}", version))));
+    }
+    let body: OwnedBody<T> = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(1, &self))?;
+    Ok(body.0)
+  }
+}
+
+/// Implements `Versioned`, `Serialize` and `Deserialize` for a type whose serde derive is
+/// generated with `#[serde(remote = "Self")]`. With `@serde`, only `Serialize` and `Deserialize`
+/// are implemented, for types that implement `Versioned` themselves to check what they read.
+macro_rules! versioned {
+  (@serde $ty:ident<$($p:ident),*> where $($bound:tt)*) => {
+    impl<$($p),*> ::serde::Serialize for $ty<$($p),*> where $($bound)* {
+      fn serialize<S: ::serde::Serializer>(&self, s: S) -> ::std::result::Result<S::Ok, S::Error> {
+        $crate::serialization::serialize_versioned(self, s)
+      }
+    }
+
+    impl<'de, $($p),*> ::serde::Deserialize<'de> for $ty<$($p),*> where $($bound)* {
+      fn deserialize<D: ::serde::Deserializer<'de>>(d: D) -> ::std::result::Result<Self, D::Error> {
+        $crate::serialization::deserialize_versioned(d)
+      }
+    }
+  };
+  ($ty:ident<$($p:ident),*> where $($bound:tt)*) => {
+    impl<$($p),*> $crate::serialization::Versioned for $ty<$($p),*> where $($bound)* {
+      fn serialize_body<S: ::serde::Serializer>(&self, s: S) -> ::std::result::Result<S::Ok, S::Error> {
+        $ty::serialize(self, s)
+      }
+
+      fn deserialize_body<'de, D: ::serde::Deserializer<'de>>(d: D) -> ::std::result::Result<Self, D::Error> {
+        $ty::deserialize(d)
+      }
+    }
+
+    $crate::serialization::versioned!(@serde $ty<$($p),*> where $($bound)*);
+  };
+}
+pub(crate) use versioned;
+
+/// Serializes an `Integer` as the byte string written by `append_integer`.
+pub mod integer {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(x: &Integer, s: S) -> Result<S::Ok, S::Error> {
+    let mut bytes = Vec::new();
+    append_integer(&mut bytes, x);
+    s.serialize_bytes(&bytes)
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Integer, D::Error> {
+    let bytes = Vec::<u8>::deserialize(d)?;
+    let mut rest = &bytes[..];
+    match read_integer(&mut rest) {
+      Some(x) if rest.is_empty() => Ok(x),
+      _ => Err(D::Error::custom(Error::Deserialization("invalid integer encoding".into()))),
+    }
+  }
+}
+
+/// Serializes a group element as the byte string given by `ElemToBytes`.
+pub fn serialize_elem<G: ElemToBytes, S: Serializer>(x: &G::Elem, s: S) -> Result<S::Ok, S::Error> {
+  s.serialize_bytes(&G::elem_to_bytes(x))
+}
+
+/// Deserializes a group element, failing unless it is the canonical encoding of an element.
+pub fn deserialize_elem<'de, G: ElemToBytes, D: Deserializer<'de>>(d: D) -> Result<G::Elem, D::Error> {
+  let bytes = Vec::<u8>::deserialize(d)?;
+  G::elem_from_bytes(&bytes).ok_or_else(invalid_elem::<D::Error>)
+}
+
+/// Serializes a sequence of group elements, each as the byte string given by `ElemToBytes`.
+pub fn serialize_elems<G: ElemToBytes, S: Serializer>(xs: &[G::Elem], s: S) -> Result<S::Ok, S::Error> {
+  s.collect_seq(xs.iter().map(G::elem_to_bytes))
+}
+
+/// Deserializes a sequence of group elements, failing if any of them is invalid.
+pub fn deserialize_elems<'de, G: ElemToBytes, D: Deserializer<'de>>(d: D) -> Result<Vec<G::Elem>, D::Error> {
+  Vec::<Vec<u8>>::deserialize(d)?
+    .iter()
+    .map(|bytes| G::elem_from_bytes(bytes).ok_or_else(invalid_elem::<D::Error>))
+    .collect()
+}
+
+// The error for bytes that are not the encoding of a group element.
+fn invalid_elem<E: serde::de::Error>() -> E {
+  E::custom(Error::Deserialization("invalid group element encoding".into()))
+}
@@ -0,0 +1,151 @@
+//! Fiat-Shamir transcripts for the crate's non-interactive proofs.
+use crate::groups::{ElemToBytes, HashPrime};
+use crate::util::append_integer;
+use rug::integer::Order;
+use rug::Integer;
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+
+/// A running hash of every message of a proof, from which the verifier's challenges are derived.
+///
+/// Each message is absorbed together with a label, and each transcript starts from a domain
+/// separator naming the protocol, so challenges of different protocols, or of different points of
+/// one protocol, are independent. Group elements are absorbed in their canonical `ElemToBytes`
+/// encoding, and every challenge is absorbed right after it is squeezed, so later challenges
+/// depend on earlier ones.
+#[derive(Clone)]
+pub struct Transcript<G> {
+  phantom: PhantomData<G>,
+  hasher: Sha256,
+}
+
+impl<G: ElemToBytes + HashPrime> Transcript<G> {
+  /// Starts a transcript for the protocol named `domain`.
+  pub fn new(domain: &[u8]) -> Transcript<G> {
+    let mut transcript = Transcript { phantom: PhantomData, hasher: Sha256::new() };
+    transcript.append_message(b"supersonic.transcript", domain);
+    transcript
+  }
+
+  /// Absorbs a byte string.
+  pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+    for bytes in &[label, message] {
+      self.hasher.update((bytes.len() as u64).to_be_bytes());
+      self.hasher.update(bytes);
+    }
+  }
+
+  /// Absorbs a group element.
+  pub fn append_elem(&mut self, label: &[u8], x: &G::Elem) {
+    self.append_message(label, &G::elem_to_bytes(x));
+  }
+
+  /// Absorbs an integer.
+  pub fn append_integer(&mut self, label: &[u8], x: &Integer) {
+    let mut bytes = Vec::new();
+    append_integer(&mut bytes, x);
+    self.append_message(label, &bytes);
+  }
+
+  /// Absorbs a length or index.
+  pub fn append_u64(&mut self, label: &[u8], n: u64) {
+    self.append_message(label, &n.to_be_bytes());
+  }
+
+  /// Squeezes a challenge uniform in `[0, 2^bits)`.
+  pub fn challenge_bits(&mut self, label: &[u8], bits: u32) -> Integer {
+    let bytes = self.squeeze(label, (bits as usize).div_ceil(8));
+    let challenge = Integer::from_digits(&bytes[..], Order::Msf).keep_bits(bits);
+    self.append_integer(label, &challenge);
+    challenge
+  }
+
+  /// Squeezes a prime challenge with `HashPrime`.
+  pub fn challenge_prime(&mut self, label: &[u8]) -> Integer {
+    let prime = G::pick_prime_integer(&self.squeeze(label, 32));
+    self.append_integer(label, &prime);
+    prime
+  }
+
+  // Expands the current state into `len` bytes with SHA-256 in counter mode.
+  fn squeeze(&self, label: &[u8], len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len + 32);
+    let mut counter = 0u64;
+    while bytes.len() < len {
+      let mut hasher = self.hasher.clone();
+      hasher.update(b"squeeze");
+      hasher.update((label.len() as u64).to_be_bytes());
+      hasher.update(label);
+      hasher.update(counter.to_be_bytes());
+      bytes.extend_from_slice(&hasher.finalize());
+      counter += 1;
+    }
+    bytes.truncate(len);
+    bytes
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  type ClassGroup = crate::groups::classgroup::ClassGroup;
+  use crate::groups::UnknownOrderGroup;
+  use rug::integer::IsPrime;
+
+  fn transcript() -> Transcript<ClassGroup> {
+    let mut transcript = Transcript::new(b"test");
+    transcript.append_elem(b"g", &ClassGroup::unknown_order_elemnew());
+    transcript.append_integer(b"x", &Integer::from(-42));
+    transcript
+  }
+
+  #[test]
+  fn test_deterministic() {
+    let (mut t_1, mut t_2) = (transcript(), transcript());
+    for bits in &[1, 8, 128, 300] {
+      assert_eq!(t_1.challenge_bits(b"c", *bits), t_2.challenge_bits(b"c", *bits));
+    }
+    assert_eq!(t_1.challenge_prime(b"l"), t_2.challenge_prime(b"l"));
+  }
+
+  #[test]
+  fn test_separation() {
+    let challenge = transcript().challenge_bits(b"c", 128);
+
+    let mut other_domain = Transcript::<ClassGroup>::new(b"tesu");
+    other_domain.append_elem(b"g", &ClassGroup::unknown_order_elemnew());
+    other_domain.append_integer(b"x", &Integer::from(-42));
+    assert_ne!(other_domain.challenge_bits(b"c", 128), challenge);
+
+    let mut other_message = transcript();
+    other_message.append_u64(b"n", 0);
+    assert_ne!(other_message.challenge_bits(b"c", 128), challenge);
+
+    // Labels and messages are length-prefixed, so moving a byte between them matters.
+    let (mut t_1, mut t_2) = (transcript(), transcript());
+    t_1.append_message(b"ab", b"c");
+    t_2.append_message(b"a", b"bc");
+    assert_ne!(t_1.challenge_bits(b"c", 128), t_2.challenge_bits(b"c", 128));
+
+    assert_ne!(transcript().challenge_bits(b"d", 128), challenge);
+  }
+
+  #[test]
+  fn test_challenges_chain() {
+    let mut transcript = transcript();
+    let c_1 = transcript.challenge_bits(b"c", 128);
+    let c_2 = transcript.challenge_bits(b"c", 128);
+    assert_ne!(c_1, c_2);
+  }
+
+  #[test]
+  fn test_challenge_range() {
+    let mut transcript = transcript();
+    for bits in 1..70 {
+      let c = transcript.challenge_bits(b"c", bits);
+      assert!(c >= 0 && c.significant_bits() <= bits);
+    }
+    let l = transcript.challenge_prime(b"l");
+    assert_ne!(l.is_probably_prime(50), IsPrime::No);
+  }
+}
@@ -1,3 +1,4 @@
+use rug::integer::Order;
 use rug::Integer;
 
 /// Pseudo-type-level programming.
@@ -16,4 +17,84 @@ where
   Integer: From<T>,
 {
   Integer::from(val)
-}
\ No newline at end of file
+}
+
+/// Runs `a` and `b` in parallel with the `parallel` feature, and one after the other without it.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+  A: FnOnce() -> RA + Send,
+  B: FnOnce() -> RB + Send,
+  RA: Send,
+  RB: Send,
+{
+  #[cfg(feature = "parallel")]
+  return rayon::join(a, b);
+  #[cfg(not(feature = "parallel"))]
+  return (a(), b());
+}
+
+/// Appends `val` to `bytes` as a sign byte followed by its length-prefixed big-endian magnitude.
+pub fn append_integer(bytes: &mut Vec<u8>, val: &Integer) {
+  let digits = val.to_digits::<u8>(Order::Msf);
+  bytes.push(if *val < 0 { 1 } else { 0 });
+  bytes.extend_from_slice(&(digits.len() as u64).to_be_bytes());
+  bytes.extend_from_slice(&digits);
+}
+
+/// Reads an integer written by `append_integer` off the front of `bytes`, or returns `None` if
+/// `bytes` does not start with one. Only the canonical encoding, without leading zero bytes or a
+/// negative zero, is accepted.
+pub fn read_integer(bytes: &mut &[u8]) -> Option<Integer> {
+  if bytes.len() < 9 || bytes[0] > 1 {
+    return None;
+  }
+  let mut len = [0u8; 8];
+  len.copy_from_slice(&bytes[1..9]);
+  let len = u64::from_be_bytes(len);
+  if len > (bytes.len() - 9) as u64 {
+    return None;
+  }
+  let (digits, rest) = bytes[9..].split_at(len as usize);
+  if digits.first() == Some(&0) || (bytes[0] == 1 && digits.is_empty()) {
+    return None;
+  }
+  let val = Integer::from_digits(digits, Order::Msf);
+  let val = if bytes[0] == 1 { -val } else { val };
+  *bytes = rest;
+  Some(val)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_read_integer() {
+    let vals = [int(0), int(1), int(-1), int(255), int(256), int(-65537), int(1) << 300];
+    let mut bytes = Vec::new();
+    for val in &vals {
+      append_integer(&mut bytes, val);
+    }
+    let mut rest = &bytes[..];
+    for val in &vals {
+      assert_eq!(read_integer(&mut rest).as_ref(), Some(val));
+    }
+    assert!(rest.is_empty());
+    assert_eq!(read_integer(&mut rest), None);
+  }
+
+  #[test]
+  fn test_read_integer_non_canonical() {
+    let mut bytes = Vec::new();
+    append_integer(&mut bytes, &int(5));
+    for bad in &[
+      vec![2, 0, 0, 0, 0, 0, 0, 0, 1, 5],
+      vec![0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 5],
+      vec![1, 0, 0, 0, 0, 0, 0, 0, 0],
+      vec![0, 0, 0, 0, 0, 0, 0, 0, 2, 5],
+      bytes[..bytes.len() - 1].to_vec(),
+    ] {
+      assert_eq!(read_integer(&mut &bad[..]), None);
+    }
+  }
+}